@@ -0,0 +1,235 @@
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use solana_instruction::{AccountMeta, Instruction};
+#[cfg(feature = "std")]
+use solana_pubkey::Pubkey;
+
+#[cfg(feature = "std")]
+use crate::{
+    find_escrow_address, find_vault_address, get_associated_token_address,
+    ASSOCIATED_TOKEN_PROGRAM_ID, ID, RENT_SYSVAR_ID, SYSTEM_PROGRAM_ID,
+};
+
+pub const MAKE_DISCRIMINATOR: u8 = 0;
+pub const TAKE_DISCRIMINATOR: u8 = 1;
+pub const CANCEL_DISCRIMINATOR: u8 = 2;
+
+// `None` leaves the escrow without a lock or an expiry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MakeArgs {
+    pub seed: u64,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    pub not_before: Option<i64>,
+    pub expires_at: Option<i64>,
+}
+
+impl MakeArgs {
+    // discriminator (1) + bump (1) + seed (8) + amount_to_receive (8) + amount_to_give (8)
+    // + not_before (8) + expires_at (8)
+    pub const LEN: usize = 1 + 1 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn data(&self, bump: u8) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::LEN);
+        data.push(MAKE_DISCRIMINATOR);
+        data.push(bump);
+        data.extend_from_slice(&self.seed.to_le_bytes());
+        data.extend_from_slice(&self.amount_to_receive.to_le_bytes());
+        data.extend_from_slice(&self.amount_to_give.to_le_bytes());
+        data.extend_from_slice(&self.not_before.unwrap_or(0).to_le_bytes());
+        data.extend_from_slice(&self.expires_at.unwrap_or(0).to_le_bytes());
+        data
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TakeArgs {
+    pub seed: u64,
+}
+
+impl TakeArgs {
+    // discriminator (1) + bump (1)
+    pub const LEN: usize = 1 + 1;
+
+    pub fn data(&self, bump: u8) -> Vec<u8> {
+        vec![TAKE_DISCRIMINATOR, bump]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CancelArgs {
+    pub seed: u64,
+}
+
+impl CancelArgs {
+    // discriminator (1) + bump (1)
+    pub const LEN: usize = 1 + 1;
+
+    pub fn data(&self, bump: u8) -> Vec<u8> {
+        vec![CANCEL_DISCRIMINATOR, bump]
+    }
+}
+
+// `solana_instruction::Instruction` needs `std`, so the builders below do too
+//
+// Each mint and its token accounts belong to its own token program, legacy SPL and Token-2022
+// mints can be paired
+#[cfg(feature = "std")]
+pub fn make(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    (token_program_a, token_program_b): (&Pubkey, &Pubkey),
+    args: &MakeArgs,
+) -> Instruction {
+    let (escrow, bump) = find_escrow_address(maker, args.seed);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(
+                get_associated_token_address(maker, mint_a, token_program_a),
+                false,
+            ),
+            AccountMeta::new(find_vault_address(&escrow, mint_a, token_program_a), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*token_program_a, false),
+            AccountMeta::new_readonly(*token_program_b, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(RENT_SYSVAR_ID, false),
+        ],
+        data: args.data(bump),
+    }
+}
+
+// The taker pays mint B from and receives mint A into their associated token accounts
+#[cfg(feature = "std")]
+pub fn take(
+    taker: &Pubkey,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    (token_program_a, token_program_b): (&Pubkey, &Pubkey),
+    args: &TakeArgs,
+) -> Instruction {
+    let (escrow, bump) = find_escrow_address(maker, args.seed);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*taker, true),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new(
+                get_associated_token_address(taker, mint_a, token_program_a),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address(taker, mint_b, token_program_b),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address(maker, mint_b, token_program_b),
+                false,
+            ),
+            AccountMeta::new(find_vault_address(&escrow, mint_a, token_program_a), false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*token_program_a, false),
+            AccountMeta::new_readonly(*token_program_b, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(RENT_SYSVAR_ID, false),
+        ],
+        data: args.data(bump),
+    }
+}
+
+// `token_program` is the one of mint A
+#[cfg(feature = "std")]
+pub fn cancel(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    token_program: &Pubkey,
+    args: &CancelArgs,
+) -> Instruction {
+    let (escrow, bump) = find_escrow_address(maker, args.seed);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new(
+                get_associated_token_address(maker, mint_a, token_program),
+                false,
+            ),
+            AccountMeta::new(find_vault_address(&escrow, mint_a, token_program), false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(RENT_SYSVAR_ID, false),
+        ],
+        data: args.data(bump),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::TOKEN_PROGRAM_ID;
+
+    #[test]
+    fn test_make_data_layout() {
+        let args = MakeArgs {
+            seed: 7,
+            amount_to_receive: 100,
+            amount_to_give: 500,
+            not_before: None,
+            expires_at: Some(1000),
+        };
+        let data = args.data(254);
+
+        assert_eq!(data.len(), MakeArgs::LEN);
+        assert_eq!(data[0], MAKE_DISCRIMINATOR);
+        assert_eq!(data[1], 254);
+        assert_eq!(&data[2..10], &7u64.to_le_bytes());
+        assert_eq!(&data[10..18], &100u64.to_le_bytes());
+        assert_eq!(&data[18..26], &500u64.to_le_bytes());
+        assert_eq!(&data[26..34], &0i64.to_le_bytes());
+        assert_eq!(&data[34..42], &1000i64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_take_and_cancel_use_the_escrow_bump() {
+        let maker = Pubkey::new_unique();
+        let taker = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let (escrow, bump) = find_escrow_address(&maker, 3);
+        let vault = find_vault_address(&escrow, &mint_a, &TOKEN_PROGRAM_ID);
+
+        let take = take(
+            &taker,
+            &maker,
+            &mint_a,
+            &mint_b,
+            (&TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID),
+            &TakeArgs { seed: 3 },
+        );
+        assert_eq!(take.data, [TAKE_DISCRIMINATOR, bump]);
+        assert_eq!(take.accounts[7].pubkey, vault);
+        assert_eq!(take.accounts[8].pubkey, escrow);
+
+        let cancel = cancel(&maker, &mint_a, &TOKEN_PROGRAM_ID, &CancelArgs { seed: 3 });
+        assert_eq!(cancel.data, [CANCEL_DISCRIMINATOR, bump]);
+        assert_eq!(cancel.accounts[3].pubkey, vault);
+        assert_eq!(cancel.accounts[4].pubkey, escrow);
+    }
+}
@@ -0,0 +1,29 @@
+//! Instruction builders and account decoders for the pinocchio escrow, so off-chain code does
+//! not have to copy its byte offsets. Without the default `std` feature only the instruction data,
+//! the addresses and the decoders are available.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod instructions;
+pub mod pda;
+pub mod state;
+
+pub use instructions::*;
+pub use pda::*;
+pub use state::*;
+
+use solana_pubkey::Pubkey;
+
+pub const ID: Pubkey = Pubkey::from_str_const("4ibrEMW5F6hKnkW4jVedswYv6H6VtwPN6ar6dvXDN1nT");
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::from_str_const("11111111111111111111111111111111");
+pub const RENT_SYSVAR_ID: Pubkey =
+    Pubkey::from_str_const("SysvarRent111111111111111111111111111111111");
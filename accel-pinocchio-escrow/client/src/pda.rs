@@ -0,0 +1,25 @@
+use solana_pubkey::Pubkey;
+
+use crate::{ASSOCIATED_TOKEN_PROGRAM_ID, ID};
+
+// The escrow lives at [b"escrow", maker, seed]
+pub fn find_escrow_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escrow", maker.as_ref(), &seed.to_le_bytes()], &ID)
+}
+
+pub fn get_associated_token_address(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// The vault is the associated token account of the escrow for mint A
+pub fn find_vault_address(escrow: &Pubkey, mint_a: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address(escrow, mint_a, token_program)
+}
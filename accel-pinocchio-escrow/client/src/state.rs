@@ -0,0 +1,127 @@
+use solana_pubkey::Pubkey;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidLength,
+    InvalidDiscriminator,
+    UnsupportedVersion,
+}
+
+// Decoded copy of the on-chain `Escrow`, a zero lock or expiry reads as `None`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Escrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    pub not_before: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl Escrow {
+    pub const DISCRIMINATOR: u8 = 1;
+    pub const VERSION: u8 = 2;
+    // discriminator (1) + version (1) + seed (8) + maker (32) + mint_a (32) + mint_b (32)
+    // + amount_to_receive (8) + amount_to_give (8) + not_before (8) + expires_at (8) + bump (1)
+    pub const LEN: usize = 1 + 1 + 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        if data.len() != Self::LEN {
+            return Err(DecodeError::InvalidLength);
+        }
+        if data[0] != Self::DISCRIMINATOR {
+            return Err(DecodeError::InvalidDiscriminator);
+        }
+        if data[1] != Self::VERSION {
+            return Err(DecodeError::UnsupportedVersion);
+        }
+
+        let u64_at = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let pubkey_at = |offset: usize| {
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(&data[offset..offset + 32]);
+            Pubkey::new_from_array(bytes)
+        };
+        let timestamp_at = |offset: usize| Some(u64_at(offset) as i64).filter(|t| *t != 0);
+
+        Ok(Self {
+            seed: u64_at(2),
+            maker: pubkey_at(10),
+            mint_a: pubkey_at(42),
+            mint_b: pubkey_at(74),
+            amount_to_receive: u64_at(106),
+            amount_to_give: u64_at(114),
+            not_before: timestamp_at(122),
+            expires_at: timestamp_at(130),
+            bump: data[138],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn escrow_bytes(version: u8) -> Vec<u8> {
+        let maker = Pubkey::new_from_array([1; 32]);
+        let mint_a = Pubkey::new_from_array([2; 32]);
+        let mint_b = Pubkey::new_from_array([3; 32]);
+
+        [
+            &[Escrow::DISCRIMINATOR, version][..],
+            &9u64.to_le_bytes(),
+            maker.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &100u64.to_le_bytes(),
+            &500u64.to_le_bytes(),
+            &200i64.to_le_bytes(),
+            &0i64.to_le_bytes(),
+            &[255],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_decode_escrow() {
+        let escrow = Escrow::try_from_bytes(&escrow_bytes(Escrow::VERSION)).unwrap();
+
+        assert_eq!(escrow.seed, 9);
+        assert_eq!(escrow.maker, Pubkey::new_from_array([1; 32]));
+        assert_eq!(escrow.mint_a, Pubkey::new_from_array([2; 32]));
+        assert_eq!(escrow.mint_b, Pubkey::new_from_array([3; 32]));
+        assert_eq!(escrow.amount_to_receive, 100);
+        assert_eq!(escrow.amount_to_give, 500);
+        assert_eq!(escrow.not_before, Some(200));
+        assert_eq!(escrow.expires_at, None);
+        assert_eq!(escrow.bump, 255);
+    }
+
+    #[test]
+    fn test_decode_rejects_other_layouts() {
+        assert_eq!(
+            Escrow::try_from_bytes(&escrow_bytes(Escrow::VERSION)[1..]),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            Escrow::try_from_bytes(&escrow_bytes(1)),
+            Err(DecodeError::UnsupportedVersion)
+        );
+
+        let mut data = escrow_bytes(Escrow::VERSION);
+        data[0] = 0;
+        assert_eq!(
+            Escrow::try_from_bytes(&data),
+            Err(DecodeError::InvalidDiscriminator)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::program_error::ProgramError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EscrowError {
+    InvalidInstructionData = 0,
+    InvalidAccountDiscriminator = 1,
+    UnsupportedVersion = 2,
+    InvalidEscrowAddress = 3,
+    InvalidVaultAddress = 4,
+    InvalidTokenAccount = 5,
+    MintMismatch = 6,
+    MakerMismatch = 7,
+    InvalidProgram = 8,
+    MissingSignature = 9,
+    InvalidAmount = 10,
+    EscrowAlreadyExists = 11,
+    NeedToWait = 12,
+    Expired = 13,
+    InvalidWindow = 14,
+    InvalidMint = 15,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(error: EscrowError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
@@ -0,0 +1,205 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+use pinocchio_pubkey::{derive_address, pubkey};
+
+use crate::error::EscrowError;
+
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer() {
+        return Err(EscrowError::MissingSignature.into());
+    }
+
+    Ok(())
+}
+
+pub fn check_program(account: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account.key() != program_id {
+        return Err(EscrowError::InvalidProgram.into());
+    }
+
+    Ok(())
+}
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+// Base layouts shared by both token programs, Token-2022 appends its extensions after them
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+// Each mint of an escrow may live under either token program, its token accounts live under the
+// same one
+pub fn check_token_program(account: &AccountInfo) -> ProgramResult {
+    if account.key() != &pinocchio_token::ID && account.key() != &TOKEN_2022_PROGRAM_ID {
+        return Err(EscrowError::InvalidProgram.into());
+    }
+
+    Ok(())
+}
+
+// An account with extensions carries its type right after the base token account layout
+fn has_layout(data: &[u8], base_len: usize, account_type: u8) -> bool {
+    data.len() == base_len
+        || (data.len() > TOKEN_ACCOUNT_LEN && data[TOKEN_ACCOUNT_LEN] == account_type)
+}
+
+// Checks that `mint` is an initialized mint of `token_program` and returns its decimals
+pub fn mint_decimals(mint: &AccountInfo, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    if !mint.is_owned_by(token_program.key()) {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    let data = mint.try_borrow_data()?;
+    // decimals (44) and is_initialized (45) follow the mint authority and the supply
+    if !has_layout(&data, MINT_LEN, ACCOUNT_TYPE_MINT) || data[45] != 1 {
+        return Err(EscrowError::InvalidMint.into());
+    }
+
+    Ok(data[44])
+}
+
+// The escrow lives at [b"escrow", maker, seed, bump]
+pub fn check_escrow_address(
+    escrow: &AccountInfo,
+    maker: &Pubkey,
+    seed: u64,
+    bump: u8,
+) -> ProgramResult {
+    let escrow_pda = derive_address(
+        &[
+            b"escrow".as_ref(),
+            maker.as_ref(),
+            &seed.to_le_bytes(),
+            &[bump],
+        ],
+        None,
+        &crate::ID,
+    );
+    if escrow_pda != *escrow.key() {
+        return Err(EscrowError::InvalidEscrowAddress.into());
+    }
+
+    Ok(())
+}
+
+// The vault is the associated token account of the escrow for mint A
+pub fn check_vault_address(
+    vault: &AccountInfo,
+    escrow: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> ProgramResult {
+    let (vault_address, _) = find_program_address(
+        &[escrow.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &pinocchio_associated_token_account::ID,
+    );
+    if vault_address != *vault.key() {
+        return Err(EscrowError::InvalidVaultAddress.into());
+    }
+
+    Ok(())
+}
+
+// Checks the owner and mint of a token account and returns its balance
+pub fn check_token_account(
+    account: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    if !account.is_owned_by(token_program.key()) {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+
+    let data = account.try_borrow_data()?;
+    // mint (0..32), owner (32..64), amount (64..72) and the account state (108)
+    if !has_layout(&data, TOKEN_ACCOUNT_LEN, ACCOUNT_TYPE_ACCOUNT) || data[108] == 0 {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+    if &data[32..64] != owner.as_ref() {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+    if &data[0..32] != mint.as_ref() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    read_u64(&data, 64)
+}
+
+// `pinocchio_token` only targets the legacy program, so the token CPIs are built by hand
+// against whichever token program the escrow uses
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked(
+    from: &AccountInfo,
+    mint: &AccountInfo,
+    to: &AccountInfo,
+    authority: &AccountInfo,
+    amount: u64,
+    decimals: u8,
+    token_program: &AccountInfo,
+    signers: &[Signer],
+) -> ProgramResult {
+    // TransferChecked (12), amount (8), decimals (1)
+    let mut data = [0u8; 10];
+    data[0] = 12;
+    data[1..9].copy_from_slice(&amount.to_le_bytes());
+    data[9] = decimals;
+
+    let instruction = Instruction {
+        program_id: token_program.key(),
+        accounts: &[
+            AccountMeta::writable(from.key()),
+            AccountMeta::readonly(mint.key()),
+            AccountMeta::writable(to.key()),
+            AccountMeta::readonly_signer(authority.key()),
+        ],
+        data: &data,
+    };
+
+    invoke_signed(&instruction, &[from, mint, to, authority], signers)
+}
+
+pub fn close_account(
+    account: &AccountInfo,
+    destination: &AccountInfo,
+    authority: &AccountInfo,
+    token_program: &AccountInfo,
+    signers: &[Signer],
+) -> ProgramResult {
+    // CloseAccount (9)
+    let instruction = Instruction {
+        program_id: token_program.key(),
+        accounts: &[
+            AccountMeta::writable(account.key()),
+            AccountMeta::writable(destination.key()),
+            AccountMeta::readonly_signer(authority.key()),
+        ],
+        data: &[9],
+    };
+
+    invoke_signed(&instruction, &[account, destination, authority], signers)
+}
+
+// Sends the escrow rent to `destination` and hands the account back to the system program
+pub fn close_escrow(escrow: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    *destination.try_borrow_mut_lamports()? += escrow.lamports();
+    escrow.close()
+}
+
+pub fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+    data.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(EscrowError::InvalidInstructionData.into())
+}
+
+pub fn read_i64(data: &[u8], offset: usize) -> Result<i64, ProgramError> {
+    read_u64(data, offset).map(|value| value as i64)
+}
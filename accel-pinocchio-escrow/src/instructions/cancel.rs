@@ -1,97 +1,115 @@
-use std::ops::{Add, Sub};
-
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     msg,
-    pubkey::{log, Pubkey},
-    sysvars::{rent::Rent, Sysvar},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_pubkey::derive_address;
 
+use crate::error::EscrowError;
+use crate::helpers::{
+    check_escrow_address, check_program, check_signer, check_token_account, check_token_program,
+    check_vault_address, close_account, close_escrow, mint_decimals, transfer_checked,
+};
 use crate::state::Escrow;
 
+// bump (1)
+pub struct CancelInstructionData {
+    pub bump: u8,
+}
+
+impl CancelInstructionData {
+    pub const LEN: usize = 1;
+}
+
+impl TryFrom<&[u8]> for CancelInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(EscrowError::InvalidInstructionData.into());
+        }
+
+        Ok(Self { bump: data[0] })
+    }
+}
+
 pub fn process_cancel_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     msg!("Processing Cancel instruction");
 
-    let [maker, mint_a, maker_ata_a, escrow_ata_a, escrow_account, system_program, token_program, _associated_token_program, _rent_sysvar @ ..] =
+    let [maker, mint_a, maker_ata_a, escrow_ata_a, escrow_account, system_program, token_program_a, _associated_token_program, _rent_sysvar @ ..] =
         accounts
     else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
     };
 
-    {
-        let escrow_ata_a_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&escrow_ata_a)?;
-        if escrow_ata_a_state.owner() != escrow_account.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-        }
-        if escrow_ata_a_state.mint() != mint_a.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-        }
-    }
+    let cancel = CancelInstructionData::try_from(data)?;
 
-    {
-        let maker_ata_a_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&maker_ata_a)?;
-        if maker_ata_a_state.owner() != maker.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
+    check_signer(maker)?;
+    check_program(system_program, &pinocchio_system::ID)?;
+    check_token_program(token_program_a)?;
+
+    let (escrow_seed, bump) = {
+        let escrow_state = Escrow::load(escrow_account)?;
+        if escrow_state.maker() != *maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
         }
-        if maker_ata_a_state.mint() != mint_a.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
+        if escrow_state.mint_a() != *mint_a.key() {
+            return Err(EscrowError::MintMismatch.into());
         }
-    }
-    if escrow_account.owner() != &crate::ID {
-        return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-    }
-
-    let bump = data[0];
-    let seed = [b"escrow".as_ref(), maker.key().as_slice(), &[bump]];
-    let seeds = &seed[..];
-
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID);
-    log(&escrow_account_pda);
-    log(&escrow_account.key());
-    assert_eq!(escrow_account_pda, *escrow_account.key());
-
-    let escrow_state = Escrow::from_account_info(escrow_account)?;
-
-    if escrow_state.maker() != *maker.key() {
-        return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-    }
-
-    let amount_to_give = escrow_state.amount_to_give();
+        if escrow_state.bump != cancel.bump {
+            return Err(EscrowError::InvalidEscrowAddress.into());
+        }
+        escrow_state.check_unlocked(Clock::get()?.unix_timestamp)?;
 
-    let bump = [bump.to_le()];
+        (escrow_state.seed(), escrow_state.bump)
+    };
+    check_escrow_address(escrow_account, maker.key(), escrow_seed, bump)?;
+
+    check_vault_address(
+        escrow_ata_a,
+        escrow_account.key(),
+        mint_a.key(),
+        token_program_a.key(),
+    )?;
+    let decimals_a = mint_decimals(mint_a, token_program_a)?;
+    let amount_to_give = check_token_account(
+        escrow_ata_a,
+        escrow_account.key(),
+        mint_a.key(),
+        token_program_a,
+    )?;
+    check_token_account(maker_ata_a, maker.key(), mint_a.key(), token_program_a)?;
+
+    let seed_bytes = escrow_seed.to_le_bytes();
+    let bump = [bump];
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.key()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump),
     ];
     let seeds = Signer::from(&seed);
 
-    pinocchio_token::instructions::Transfer {
-        from: escrow_ata_a,
-        to: maker_ata_a,
-        authority: escrow_account,
-        amount: amount_to_give,
-    }
-    .invoke_signed(&[seeds.clone()])?;
-
-    // Move lamports to maker
-    let lamports_to_transfer = escrow_account.lamports();
-
-    let mut lamport_maker_mut = maker.try_borrow_mut_lamports().unwrap();
-    *lamport_maker_mut += lamports_to_transfer;
-
-    {
-        let mut lamport_escrow_mut = escrow_account.try_borrow_mut_lamports().unwrap();
-        *lamport_escrow_mut = 0; // zero escrow lamports
-    }
-
-    // Close account signaling (depends on Pinocchio API, may be manual)
-    escrow_account.close()?;
-
-    Ok(())
+    transfer_checked(
+        escrow_ata_a,
+        mint_a,
+        maker_ata_a,
+        escrow_account,
+        amount_to_give,
+        decimals_a,
+        token_program_a,
+        &[seeds.clone()],
+    )?;
+
+    close_account(
+        escrow_ata_a,
+        maker,
+        escrow_account,
+        token_program_a,
+        &[seeds],
+    )?;
+
+    close_escrow(escrow_account, maker)
 }
@@ -2,101 +2,151 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     msg,
-    pubkey::log,
-    sysvars::{rent::Rent, Sysvar},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
-use pinocchio_pubkey::derive_address;
 use pinocchio_system::instructions::CreateAccount;
 
+use crate::error::EscrowError;
+use crate::helpers::{
+    check_escrow_address, check_program, check_signer, check_token_account, check_token_program,
+    mint_decimals, read_i64, read_u64, transfer_checked,
+};
 use crate::state::Escrow;
 
+// bump (1) + seed (8) + amount_to_receive (8) + amount_to_give (8) + not_before (8) + expires_at (8)
+// A zero not_before or expires_at leaves the escrow without a lock or an expiry
+pub struct MakeInstructionData {
+    pub bump: u8,
+    pub seed: u64,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    pub not_before: i64,
+    pub expires_at: i64,
+}
+
+impl MakeInstructionData {
+    pub const LEN: usize = 1 + 8 + 8 + 8 + 8 + 8;
+}
+
+impl TryFrom<&[u8]> for MakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(EscrowError::InvalidInstructionData.into());
+        }
+
+        let make = Self {
+            bump: data[0],
+            seed: read_u64(data, 1)?,
+            amount_to_receive: read_u64(data, 9)?,
+            amount_to_give: read_u64(data, 17)?,
+            not_before: read_i64(data, 25)?,
+            expires_at: read_i64(data, 33)?,
+        };
+        if make.amount_to_receive == 0 || make.amount_to_give == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+        if make.not_before < 0
+            || make.expires_at < 0
+            || (make.expires_at != 0 && make.not_before >= make.expires_at)
+        {
+            return Err(EscrowError::InvalidWindow.into());
+        }
+
+        Ok(make)
+    }
+}
+
 pub fn process_make_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     msg!("Processing Make instruction");
 
-    let [maker, mint_a, mint_b, escrow_account, maker_ata, escrow_ata, system_program, token_program, _associated_token_program, _rent_sysvar @ ..] =
+    let [maker, mint_a, mint_b, escrow_account, maker_ata, escrow_ata, system_program, token_program_a, token_program_b, associated_token_program, _rent_sysvar @ ..] =
         accounts
     else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
     };
-    {
-        let maker_ata_state = pinocchio_token::state::TokenAccount::from_account_info(maker_ata)?;
-        if maker_ata_state.owner() != maker.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-        }
-        if maker_ata_state.mint() != mint_a.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-        }
-    }
 
-    let bump = data[0];
-    let seed = [b"escrow".as_ref(), maker.key().as_slice(), &[bump]];
-    let seeds = &seed[..];
+    let make = MakeInstructionData::try_from(data)?;
+
+    check_signer(maker)?;
+    check_program(system_program, &pinocchio_system::ID)?;
+    check_token_program(token_program_a)?;
+    check_token_program(token_program_b)?;
+    check_program(
+        associated_token_program,
+        &pinocchio_associated_token_account::ID,
+    )?;
 
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID);
-    log(&escrow_account_pda);
-    log(&escrow_account.key());
-    assert_eq!(escrow_account_pda, *escrow_account.key());
+    // each mint has to belong to the token program passed for it
+    let decimals_a = mint_decimals(mint_a, token_program_a)?;
+    mint_decimals(mint_b, token_program_b)?;
 
-    let amount_to_receive = unsafe { *(data.as_ptr().add(1) as *const u64) };
-    let amount_to_give = unsafe { *(data.as_ptr().add(9) as *const u64) };
+    check_token_account(maker_ata, maker.key(), mint_a.key(), token_program_a)?;
 
-    let bump = [bump.to_le()];
+    if make.expires_at != 0 && make.expires_at <= Clock::get()?.unix_timestamp {
+        return Err(EscrowError::InvalidWindow.into());
+    }
+
+    check_escrow_address(escrow_account, maker.key(), make.seed, make.bump)?;
+    if !escrow_account.is_owned_by(&pinocchio_system::ID) || !escrow_account.data_is_empty() {
+        return Err(EscrowError::EscrowAlreadyExists.into());
+    }
+
+    let seed_bytes = make.seed.to_le_bytes();
+    let bump = [make.bump];
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.key()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump),
     ];
     let seeds = Signer::from(&seed);
 
-    let escrow_acc = escrow_account.clone();
-    // let escrow_acc4 = escrow_account.clone();
-
-    if escrow_account.owner() != &crate::ID {
-        CreateAccount {
-            from: maker,
-            to: escrow_account,
-            lamports: Rent::get()?.minimum_balance(Escrow::LEN),
-            space: Escrow::LEN as u64,
-            owner: &crate::ID,
-        }
-        .invoke_signed(&[seeds.clone()])?;
-
-        {
-            let escrow_state = Escrow::from_account_info(escrow_account)?;
-
-            escrow_state.set_maker(maker.key());
-            escrow_state.set_mint_a(mint_a.key());
-            escrow_state.set_mint_b(mint_b.key());
-            escrow_state.set_amount_to_receive(amount_to_receive);
-            escrow_state.set_amount_to_give(amount_to_give);
-            escrow_state.bump = data[0];
-        }
-    } else {
-        return Err(pinocchio::program_error::ProgramError::IllegalOwner);
+    CreateAccount {
+        from: maker,
+        to: escrow_account,
+        lamports: Rent::get()?.minimum_balance(Escrow::LEN),
+        space: Escrow::LEN as u64,
+        owner: &crate::ID,
     }
+    .invoke_signed(&[seeds])?;
 
-    // let escrow_ata1 = escrow_ata.clone();
+    {
+        let mut escrow_state = Escrow::init(escrow_account)?;
+
+        escrow_state.set_seed(make.seed);
+        escrow_state.set_maker(maker.key());
+        escrow_state.set_mint_a(mint_a.key());
+        escrow_state.set_mint_b(mint_b.key());
+        escrow_state.set_amount_to_receive(make.amount_to_receive);
+        escrow_state.set_amount_to_give(make.amount_to_give);
+        escrow_state.set_not_before(make.not_before);
+        escrow_state.set_expires_at(make.expires_at);
+        escrow_state.bump = make.bump;
+    }
 
+    // the associated token program rejects a vault address that is not the escrow ATA
     pinocchio_associated_token_account::instructions::Create {
         funding_account: maker,
         account: escrow_ata,
         wallet: escrow_account,
         mint: mint_a,
-        token_program: token_program,
+        token_program: token_program_a,
         system_program: system_program,
     }
     .invoke()?;
 
-    // let escrow_acc2 = escrow_account.clone();
-
-    pinocchio_token::instructions::Transfer {
-        from: maker_ata,
-        to: escrow_ata,
-        authority: maker,
-        amount: amount_to_give,
-    }
-    .invoke()?;
-
-    Ok(())
+    transfer_checked(
+        maker_ata,
+        mint_a,
+        escrow_ata,
+        maker,
+        make.amount_to_give,
+        decimals_a,
+        token_program_a,
+        &[],
+    )
 }
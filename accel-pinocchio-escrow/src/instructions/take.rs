@@ -2,106 +2,133 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     msg,
-    pubkey::log,
-    sysvars::{rent::Rent, Sysvar},
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_pubkey::derive_address;
 
+use crate::error::EscrowError;
+use crate::helpers::{
+    check_escrow_address, check_program, check_signer, check_token_account, check_token_program,
+    check_vault_address, close_account, close_escrow, mint_decimals, transfer_checked,
+};
 use crate::state::Escrow;
 
+// bump (1)
+pub struct TakeInstructionData {
+    pub bump: u8,
+}
+
+impl TakeInstructionData {
+    pub const LEN: usize = 1;
+}
+
+impl TryFrom<&[u8]> for TakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(EscrowError::InvalidInstructionData.into());
+        }
+
+        Ok(Self { bump: data[0] })
+    }
+}
+
 pub fn process_take_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     msg!("Processing Take instruction");
 
-    let [taker, maker, mint_a, mint_b, taker_ata_a, taker_ata_b, maker_ata_b, escrow_ata_a, escrow_account, system_program, token_program, _associated_token_program, _rent_sysvar @ ..] =
+    let [taker, maker, mint_a, mint_b, taker_ata_a, taker_ata_b, maker_ata_b, escrow_ata_a, escrow_account, system_program, token_program_a, token_program_b, _associated_token_program, _rent_sysvar @ ..] =
         accounts
     else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
     };
 
-    {
-        let escrow_ata_a_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&escrow_ata_a)?;
-        if escrow_ata_a_state.owner() != escrow_account.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-        }
-        if escrow_ata_a_state.mint() != mint_a.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-        }
-    }
-    {
-        let maker_ata_b_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&maker_ata_b)?;
-        if maker_ata_b_state.owner() != maker.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-        }
-        if maker_ata_b_state.mint() != mint_b.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-        }
-    }
+    let take = TakeInstructionData::try_from(data)?;
 
-    {
-        let taker_ata_a_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&taker_ata_a)?;
-        if taker_ata_a_state.owner() != taker.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-        }
-        if taker_ata_a_state.mint() != mint_a.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-        }
-    }
+    check_signer(taker)?;
+    check_program(system_program, &pinocchio_system::ID)?;
+    check_token_program(token_program_a)?;
+    check_token_program(token_program_b)?;
 
-    {
-        let taker_ata_b_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&*taker_ata_b)?;
-        if taker_ata_b_state.owner() != taker.key() {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
+    let (amount_to_receive, escrow_seed, bump) = {
+        let escrow_state = Escrow::load(escrow_account)?;
+        if escrow_state.maker() != *maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
         }
-        if taker_ata_b_state.mint() != mint_b.key() {
-            return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
+        if escrow_state.mint_a() != *mint_a.key() || escrow_state.mint_b() != *mint_b.key() {
+            return Err(EscrowError::MintMismatch.into());
         }
-    }
-
-    if escrow_account.owner() != &crate::ID {
-        return Err(pinocchio::program_error::ProgramError::IllegalOwner);
-    }
-
-    let bump = data[0];
-    let seed = [b"escrow".as_ref(), maker.key().as_slice(), &[bump]];
-    let seeds = &seed[..];
+        if escrow_state.bump != take.bump {
+            return Err(EscrowError::InvalidEscrowAddress.into());
+        }
+        escrow_state.check_window(Clock::get()?.unix_timestamp)?;
 
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID);
-    log(&escrow_account_pda);
-    log(&escrow_account.key());
-    assert_eq!(escrow_account_pda, *escrow_account.key());
+        (
+            escrow_state.amount_to_receive(),
+            escrow_state.seed(),
+            escrow_state.bump,
+        )
+    };
+    check_escrow_address(escrow_account, maker.key(), escrow_seed, bump)?;
 
-    let escrow_state = Escrow::from_account_info(escrow_account)?;
-    let amount_to_receive = escrow_state.amount_to_receive();
-    let amount_to_give = escrow_state.amount_to_give();
+    check_vault_address(
+        escrow_ata_a,
+        escrow_account.key(),
+        mint_a.key(),
+        token_program_a.key(),
+    )?;
+    let decimals_a = mint_decimals(mint_a, token_program_a)?;
+    let decimals_b = mint_decimals(mint_b, token_program_b)?;
+    let amount_to_give = check_token_account(
+        escrow_ata_a,
+        escrow_account.key(),
+        mint_a.key(),
+        token_program_a,
+    )?;
+    check_token_account(maker_ata_b, maker.key(), mint_b.key(), token_program_b)?;
+    check_token_account(taker_ata_a, taker.key(), mint_a.key(), token_program_a)?;
+    check_token_account(taker_ata_b, taker.key(), mint_b.key(), token_program_b)?;
 
-    let bump = [bump.to_le()];
+    let seed_bytes = escrow_seed.to_le_bytes();
+    let bump = [bump];
     let seed = [
         Seed::from(b"escrow"),
         Seed::from(maker.key()),
+        Seed::from(&seed_bytes),
         Seed::from(&bump),
     ];
     let seeds = Signer::from(&seed);
 
-    pinocchio_token::instructions::Transfer {
-        from: taker_ata_b,
-        to: maker_ata_b,
-        authority: taker,
-        amount: amount_to_receive,
-    }
-    .invoke()?;
+    transfer_checked(
+        taker_ata_b,
+        mint_b,
+        maker_ata_b,
+        taker,
+        amount_to_receive,
+        decimals_b,
+        token_program_b,
+        &[],
+    )?;
 
-    pinocchio_token::instructions::Transfer {
-        from: escrow_ata_a,
-        to: taker_ata_a,
-        authority: escrow_account,
-        amount: amount_to_give,
-    }
-    .invoke_signed(&[seeds.clone()])?;
+    transfer_checked(
+        escrow_ata_a,
+        mint_a,
+        taker_ata_a,
+        escrow_account,
+        amount_to_give,
+        decimals_a,
+        token_program_a,
+        &[seeds.clone()],
+    )?;
+
+    close_account(
+        escrow_ata_a,
+        maker,
+        escrow_account,
+        token_program_a,
+        &[seeds],
+    )?;
 
-    Ok(())
+    close_escrow(escrow_account, maker)
 }
@@ -1,7 +1,12 @@
-use pinocchio::{account_info::AccountInfo, entrypoint, pubkey::Pubkey, ProgramResult};
+use pinocchio::{
+    account_info::AccountInfo, entrypoint, program_error::ProgramError, pubkey::Pubkey,
+    ProgramResult,
+};
 
 use crate::instructions::EscrowInstrctions;
 
+mod error;
+mod helpers;
 mod instructions;
 mod state;
 mod tests;
@@ -15,18 +20,20 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
 
     let (discriminator, data) = instruction_data
         .split_first()
-        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?;
+        .ok_or(ProgramError::InvalidInstructionData)?;
 
     match EscrowInstrctions::try_from(discriminator)? {
         EscrowInstrctions::Make => instructions::process_make_instruction(accounts, data)?,
         EscrowInstrctions::Take => instructions::process_take_instruction(accounts, data)?,
         EscrowInstrctions::Cancel => instructions::process_cancel_instruction(accounts, data)?,
 
-        _ => return Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
+        _ => return Err(ProgramError::InvalidInstructionData),
     }
     Ok(())
 }
@@ -1,60 +1,140 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
 
+use crate::error::EscrowError;
+
+// Every field is a byte or a byte array, so the struct has an alignment of 1 and can be
+// read in place from any account data of the right length
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Escrow {
+    discriminator: u8,
+    version: u8,
+    seed: [u8; 8],
     maker: [u8; 32],
     mint_a: [u8; 32],
     mint_b: [u8; 32],
     amount_to_receive: [u8; 8],
     amount_to_give: [u8; 8],
+    not_before: [u8; 8],
+    expires_at: [u8; 8],
     pub bump: u8,
 }
 
+const _: () = assert!(core::mem::align_of::<Escrow>() == 1);
+
 impl Escrow {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8;
+    pub const DISCRIMINATOR: u8 = 1;
+    pub const VERSION: u8 = 2;
+    pub const LEN: usize = core::mem::size_of::<Self>();
 
-    pub fn from_account_info(account_info: &AccountInfo) -> Result<&mut Self, ProgramError> {
-        let mut data = account_info.try_borrow_mut_data()?;
-        if data.len() != Escrow::LEN {
-            return Err(ProgramError::InvalidAccountData);
+    // Loads an initialized escrow owned by this program
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        Self::check(account_info)?;
+
+        let escrow = Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        });
+        escrow.check_header()?;
+
+        Ok(escrow)
+    }
+
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        Self::check(account_info)?;
+
+        let escrow = RefMut::map(account_info.try_borrow_mut_data()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        escrow.check_header()?;
+
+        Ok(escrow)
+    }
+
+    // Writes the header of a freshly created escrow account
+    pub fn init(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        Self::check(account_info)?;
+
+        let mut escrow = RefMut::map(account_info.try_borrow_mut_data()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        if escrow.discriminator != 0 {
+            return Err(EscrowError::EscrowAlreadyExists.into());
         }
+        escrow.discriminator = Self::DISCRIMINATOR;
+        escrow.version = Self::VERSION;
+
+        Ok(escrow)
+    }
 
-        if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
+    fn check(account_info: &AccountInfo) -> Result<(), ProgramError> {
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        if account_info.data_len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        // let mut escrow = Escrow::default();
-        
-        // escrow.maker.copy_from_slice(&data[0..32]);
-        // escrow.mint_a.copy_from_slice(&data[32..64]);
-        // escrow.mint_b.copy_from_slice(&data[64..96]);
-        // escrow.amount_to_receive.copy_from_slice(&data[96..104]);
+        Ok(())
+    }
+
+    fn check_header(&self) -> Result<(), ProgramError> {
+        if self.discriminator != Self::DISCRIMINATOR {
+            return Err(EscrowError::InvalidAccountDiscriminator.into());
+        }
+        if self.version != Self::VERSION {
+            return Err(EscrowError::UnsupportedVersion.into());
+        }
+
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` must be at least `Escrow::LEN` long.
+    unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Self)
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` must be at least `Escrow::LEN` long.
+    unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Self)
+    }
 
-        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
     }
 
-    pub fn maker(&self) -> pinocchio::pubkey::Pubkey {
-        pinocchio::pubkey::Pubkey::from(self.maker)
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed.to_le_bytes();
     }
 
-    pub fn set_maker(&mut self, maker: &pinocchio::pubkey::Pubkey) {
+    pub fn maker(&self) -> Pubkey {
+        Pubkey::from(self.maker)
+    }
+
+    pub fn set_maker(&mut self, maker: &Pubkey) {
         self.maker.copy_from_slice(maker.as_ref());
     }
 
-    pub fn mint_a(&self) -> pinocchio::pubkey::Pubkey {
-        pinocchio::pubkey::Pubkey::from(self.mint_a)
+    pub fn mint_a(&self) -> Pubkey {
+        Pubkey::from(self.mint_a)
     }
 
-    pub fn set_mint_a(&mut self, mint_a: &pinocchio::pubkey::Pubkey) {
+    pub fn set_mint_a(&mut self, mint_a: &Pubkey) {
         self.mint_a.copy_from_slice(mint_a.as_ref());
     }
 
-    pub fn mint_b(&self) -> pinocchio::pubkey::Pubkey {
-        pinocchio::pubkey::Pubkey::from(self.mint_b)
+    pub fn mint_b(&self) -> Pubkey {
+        Pubkey::from(self.mint_b)
     }
 
-    pub fn set_mint_b(&mut self, mint_b: &pinocchio::pubkey::Pubkey) {
+    pub fn set_mint_b(&mut self, mint_b: &Pubkey) {
         self.mint_b.copy_from_slice(mint_b.as_ref());
     }
 
@@ -73,4 +153,39 @@ impl Escrow {
     pub fn set_amount_to_give(&mut self, amount: u64) {
         self.amount_to_give = amount.to_le_bytes();
     }
-}
\ No newline at end of file
+
+    // 0 means the escrow has no lock
+    pub fn not_before(&self) -> i64 {
+        i64::from_le_bytes(self.not_before)
+    }
+
+    pub fn set_not_before(&mut self, not_before: i64) {
+        self.not_before = not_before.to_le_bytes();
+    }
+
+    // 0 means the escrow never expires
+    pub fn expires_at(&self) -> i64 {
+        i64::from_le_bytes(self.expires_at)
+    }
+
+    pub fn set_expires_at(&mut self, expires_at: i64) {
+        self.expires_at = expires_at.to_le_bytes();
+    }
+
+    pub fn check_unlocked(&self, now: i64) -> Result<(), ProgramError> {
+        if self.not_before() != 0 && now < self.not_before() {
+            return Err(EscrowError::NeedToWait.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn check_window(&self, now: i64) -> Result<(), ProgramError> {
+        self.check_unlocked(now)?;
+        if self.expires_at() != 0 && now >= self.expires_at() {
+            return Err(EscrowError::Expired.into());
+        }
+
+        Ok(())
+    }
+}
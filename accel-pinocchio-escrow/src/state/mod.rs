@@ -1,3 +1,3 @@
 pub mod escrow;
 
-pub use escrow::*;
\ No newline at end of file
+pub use escrow::*;
@@ -0,0 +1,551 @@
+// Runs the same escrow scenarios against this program and the Anchor escrow of `escrow-litesvm`,
+// then checks that both agree on every step and prints the compute units each one used.
+// The Anchor program is loaded from `../escrow-litesvm/target/deploy/anchor_escrow.so`.
+// The Anchor escrow only takes legacy SPL mints, pairs mixing in Token-2022 are checked against
+// the legacy pair of this program instead.
+
+use std::path::PathBuf;
+
+use litesvm::LiteSVM;
+use litesvm_token::{
+    spl_token::{
+        self,
+        solana_program::{hash::hash, msg, rent::Rent, sysvar::SysvarId},
+    },
+    CreateAssociatedTokenAccount, CreateMint, MintTo,
+};
+
+use solana_clock::Clock;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_message::Message;
+use solana_native_token::LAMPORTS_PER_SOL;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+const ANCHOR_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
+const TOKEN_2022_PROGRAM_ID: Pubkey = spl_token_2022::ID;
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk_ids::system_program::ID;
+
+const AMOUNT_TO_RECEIVE: u64 = 100000000;
+const AMOUNT_TO_GIVE: u64 = 500000000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Implementation {
+    Pinocchio,
+    Anchor,
+}
+
+impl Implementation {
+    fn program_id(&self) -> Pubkey {
+        match self {
+            Implementation::Pinocchio => Pubkey::from(crate::ID),
+            Implementation::Anchor => ANCHOR_PROGRAM_ID,
+        }
+    }
+
+    fn so_path(&self) -> PathBuf {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        match self {
+            Implementation::Pinocchio => manifest_dir.join("target/deploy/escrow.so"),
+            Implementation::Anchor => {
+                manifest_dir.join("../escrow-litesvm/target/deploy/anchor_escrow.so")
+            }
+        }
+    }
+}
+
+// Anchor instruction data starts with the first 8 bytes of sha256("global:<name>")
+fn anchor_discriminator(name: &str) -> Vec<u8> {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec()
+}
+
+fn borsh_option_i64(value: Option<i64>) -> Vec<u8> {
+    match value {
+        Some(value) => [vec![1u8], value.to_le_bytes().to_vec()].concat(),
+        None => vec![0u8],
+    }
+}
+
+struct Env {
+    svm: LiteSVM,
+    implementation: Implementation,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    maker_ata_a: Pubkey,
+    maker_ata_b: Pubkey,
+    taker_ata_a: Pubkey,
+    taker_ata_b: Pubkey,
+}
+
+impl Env {
+    fn new(
+        implementation: Implementation,
+        (token_program_a, token_program_b): (Pubkey, Pubkey),
+    ) -> Self {
+        let mut svm = LiteSVM::new();
+        let maker = Keypair::new();
+        let taker = Keypair::new();
+
+        svm.airdrop(&maker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&taker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+
+        let program_data =
+            std::fs::read(implementation.so_path()).expect("Failed to read program SO file");
+        svm.add_program(implementation.program_id(), &program_data);
+
+        let mint_a = CreateMint::new(&mut svm, &maker)
+            .decimals(6)
+            .authority(&maker.pubkey())
+            .token_program_id(&token_program_a)
+            .send()
+            .unwrap();
+        let mint_b = CreateMint::new(&mut svm, &taker)
+            .decimals(6)
+            .authority(&taker.pubkey())
+            .token_program_id(&token_program_b)
+            .send()
+            .unwrap();
+
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&maker.pubkey())
+            .token_program_id(&token_program_a)
+            .send()
+            .unwrap();
+        let maker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_b)
+            .owner(&maker.pubkey())
+            .token_program_id(&token_program_b)
+            .send()
+            .unwrap();
+        let taker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &taker, &mint_a)
+            .owner(&taker.pubkey())
+            .token_program_id(&token_program_a)
+            .send()
+            .unwrap();
+        let taker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &taker, &mint_b)
+            .owner(&taker.pubkey())
+            .token_program_id(&token_program_b)
+            .send()
+            .unwrap();
+
+        MintTo::new(&mut svm, &maker, &mint_a, &maker_ata_a, 1000000000)
+            .token_program_id(&token_program_a)
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &taker, &mint_b, &taker_ata_b, 1000000000)
+            .token_program_id(&token_program_b)
+            .send()
+            .unwrap();
+
+        Self {
+            svm,
+            implementation,
+            token_program_a,
+            token_program_b,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        }
+    }
+
+    fn escrow(&self, seed: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"escrow".as_ref(),
+                self.maker.pubkey().as_ref(),
+                &seed.to_le_bytes(),
+            ],
+            &self.implementation.program_id(),
+        )
+    }
+
+    fn vault(&self, seed: u64) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &self.escrow(seed).0,
+            &self.mint_a,
+            &self.token_program_a,
+        )
+    }
+
+    fn set_time(&mut self, unix_timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar::<Clock>(&clock);
+    }
+
+    fn make_ix(&self, seed: u64, not_before: Option<i64>, expires_at: Option<i64>) -> Instruction {
+        let (escrow, bump) = self.escrow(seed);
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new(self.mint_b, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new(SYSTEM_PROGRAM_ID, false),
+                    AccountMeta::new(self.token_program_a, false),
+                    AccountMeta::new(self.token_program_b, false),
+                    AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new(Rent::id(), false),
+                ],
+                data: [
+                    vec![0u8],
+                    vec![bump],
+                    seed.to_le_bytes().to_vec(),
+                    AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
+                    AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
+                    not_before.unwrap_or(0).to_le_bytes().to_vec(),
+                    expires_at.unwrap_or(0).to_le_bytes().to_vec(),
+                ]
+                .concat(),
+            },
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new_readonly(self.mint_a, false),
+                    AccountMeta::new_readonly(self.mint_b, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                // the window only exists on `make_with_window`, `make` keeps the old layout
+                data: if not_before.is_some() || expires_at.is_some() {
+                    [
+                        anchor_discriminator("make_with_window"),
+                        seed.to_le_bytes().to_vec(),
+                        AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
+                        AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
+                        vec![0u8], // no designated taker
+                        borsh_option_i64(not_before),
+                        borsh_option_i64(expires_at),
+                    ]
+                    .concat()
+                } else {
+                    [
+                        anchor_discriminator("make"),
+                        seed.to_le_bytes().to_vec(),
+                        AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
+                        AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
+                    ]
+                    .concat()
+                },
+            },
+        }
+    }
+
+    fn take_ix(&self, seed: u64) -> Instruction {
+        let (escrow, bump) = self.escrow(seed);
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+        let taker = self.taker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(taker, true),
+                    AccountMeta::new(maker, false),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new(self.mint_b, false),
+                    AccountMeta::new(self.taker_ata_a, false),
+                    AccountMeta::new(self.taker_ata_b, false),
+                    AccountMeta::new(self.maker_ata_b, false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(SYSTEM_PROGRAM_ID, false),
+                    AccountMeta::new(self.token_program_a, false),
+                    AccountMeta::new(self.token_program_b, false),
+                    AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new(Rent::id(), false),
+                ],
+                data: vec![1u8, bump],
+            },
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(taker, true),
+                    AccountMeta::new(maker, false),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new_readonly(self.mint_b, false),
+                    AccountMeta::new(self.taker_ata_a, false),
+                    AccountMeta::new(self.taker_ata_b, false),
+                    AccountMeta::new(self.maker_ata_b, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    // no offer book
+                    AccountMeta::new_readonly(ANCHOR_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data: anchor_discriminator("take"),
+            },
+        }
+    }
+
+    // `refund_with_interval` is the Anchor refund that honours the lock like `Cancel` does
+    fn cancel_ix(&self, seed: u64) -> Instruction {
+        let (escrow, bump) = self.escrow(seed);
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(SYSTEM_PROGRAM_ID, false),
+                    AccountMeta::new(self.token_program_a, false),
+                    AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new(Rent::id(), false),
+                ],
+                data: vec![2u8, bump],
+            },
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    // no offer book
+                    AccountMeta::new_readonly(ANCHOR_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data: anchor_discriminator("refund_with_interval"),
+            },
+        }
+    }
+
+    // Returns the compute units of a successful transaction
+    fn send(&mut self, instruction: Instruction, signer: &Keypair) -> Result<u64, ()> {
+        self.svm.expire_blockhash();
+        let message = Message::new(&[instruction], Some(&signer.pubkey()));
+        let recent_blockhash = self.svm.latest_blockhash();
+        let transaction = Transaction::new(&[signer], message, recent_blockhash);
+
+        self.svm
+            .send_transaction(transaction)
+            .map(|tx| tx.compute_units_consumed)
+            .map_err(|_| ())
+    }
+
+    fn make(
+        &mut self,
+        seed: u64,
+        not_before: Option<i64>,
+        expires_at: Option<i64>,
+    ) -> Result<u64, ()> {
+        let instruction = self.make_ix(seed, not_before, expires_at);
+        let maker = self.maker.insecure_clone();
+        self.send(instruction, &maker)
+    }
+
+    fn take(&mut self, seed: u64) -> Result<u64, ()> {
+        let instruction = self.take_ix(seed);
+        let taker = self.taker.insecure_clone();
+        self.send(instruction, &taker)
+    }
+
+    fn cancel(&mut self, seed: u64) -> Result<u64, ()> {
+        let instruction = self.cancel_ix(seed);
+        let maker = self.maker.insecure_clone();
+        self.send(instruction, &maker)
+    }
+
+    // The base layout is shared by both token programs
+    fn token_amount(&self, account: &Pubkey) -> u64 {
+        use spl_token_2022::{extension::StateWithExtensions, state::Account};
+
+        let account = self.svm.get_account(account).unwrap();
+        StateWithExtensions::<Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Scenario {
+    TakeOpenEscrow,
+    CancelOpenEscrow,
+    TakeBeforeLockEnds,
+    TakeAfterExpiry,
+    CancelBeforeLockEnds,
+    SeveralEscrowsPerMaker,
+}
+
+impl Scenario {
+    const ALL: [Scenario; 6] = [
+        Scenario::TakeOpenEscrow,
+        Scenario::CancelOpenEscrow,
+        Scenario::TakeBeforeLockEnds,
+        Scenario::TakeAfterExpiry,
+        Scenario::CancelBeforeLockEnds,
+        Scenario::SeveralEscrowsPerMaker,
+    ];
+
+    // Each step is named and yields the compute units it used, or an error
+    fn run(&self, env: &mut Env) -> Vec<(&'static str, Result<u64, ()>)> {
+        match self {
+            Scenario::TakeOpenEscrow => {
+                vec![("make", env.make(1, None, None)), ("take", env.take(1))]
+            }
+            Scenario::CancelOpenEscrow => {
+                vec![("make", env.make(1, None, None)), ("cancel", env.cancel(1))]
+            }
+            Scenario::TakeBeforeLockEnds => {
+                env.set_time(100);
+                let make = env.make(1, Some(200), None);
+                let early_take = env.take(1);
+                env.set_time(250);
+                vec![
+                    ("make", make),
+                    ("take before lock", early_take),
+                    ("take after lock", env.take(1)),
+                ]
+            }
+            Scenario::TakeAfterExpiry => {
+                env.set_time(100);
+                let make = env.make(1, None, Some(200));
+                env.set_time(250);
+                vec![
+                    ("make", make),
+                    ("take after expiry", env.take(1)),
+                    ("cancel after expiry", env.cancel(1)),
+                ]
+            }
+            Scenario::CancelBeforeLockEnds => {
+                env.set_time(100);
+                let make = env.make(1, Some(200), None);
+                let early_cancel = env.cancel(1);
+                env.set_time(250);
+                vec![
+                    ("make", make),
+                    ("cancel before lock", early_cancel),
+                    ("cancel after lock", env.cancel(1)),
+                ]
+            }
+            Scenario::SeveralEscrowsPerMaker => vec![
+                ("make seed 1", env.make(1, None, None)),
+                ("make seed 2", env.make(2, None, None)),
+                ("take seed 2", env.take(2)),
+                ("cancel seed 1", env.cancel(1)),
+            ],
+        }
+    }
+}
+
+// Runs `scenario` on both environments and checks that every step and the final balances agree
+fn assert_same_outcome(
+    scenario: Scenario,
+    (left_name, left): (&str, &mut Env),
+    (right_name, right): (&str, &mut Env),
+) {
+    let left_steps = scenario.run(left);
+    let right_steps = scenario.run(right);
+
+    msg!("\n{:?}", scenario);
+    msg!("| step | {} CUs | {} CUs |", left_name, right_name);
+    for ((step, left_result), (_, right_result)) in left_steps.iter().zip(right_steps.iter()) {
+        msg!("| {} | {:?} | {:?} |", step, left_result, right_result);
+        assert_eq!(
+            left_result.is_ok(),
+            right_result.is_ok(),
+            "{:?}: `{}` diverges between {} and {}",
+            scenario,
+            step,
+            left_name,
+            right_name
+        );
+    }
+
+    // both end with the same balances
+    for account in [
+        (left.maker_ata_a, right.maker_ata_a),
+        (left.maker_ata_b, right.maker_ata_b),
+        (left.taker_ata_a, right.taker_ata_a),
+        (left.taker_ata_b, right.taker_ata_b),
+    ] {
+        assert_eq!(
+            left.token_amount(&account.0),
+            right.token_amount(&account.1)
+        );
+    }
+}
+
+#[test]
+pub fn test_matrix_against_anchor() {
+    let legacy = (TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID);
+
+    for scenario in Scenario::ALL {
+        let mut pinocchio = Env::new(Implementation::Pinocchio, legacy);
+        let mut anchor = Env::new(Implementation::Anchor, legacy);
+
+        assert_same_outcome(
+            scenario,
+            ("pinocchio", &mut pinocchio),
+            ("anchor", &mut anchor),
+        );
+    }
+}
+
+#[test]
+pub fn test_matrix_mixed_token_programs() {
+    let legacy = (TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID);
+
+    for mixed in [
+        (TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID),
+        (TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID),
+    ] {
+        for scenario in Scenario::ALL {
+            let mut legacy_env = Env::new(Implementation::Pinocchio, legacy);
+            let mut mixed_env = Env::new(Implementation::Pinocchio, mixed);
+
+            assert_same_outcome(
+                scenario,
+                ("legacy", &mut legacy_env),
+                ("mixed", &mut mixed_env),
+            );
+        }
+
+        // each mint is checked against its own token program, so swapping them is rejected
+        let mut env = Env::new(Implementation::Pinocchio, mixed);
+        let mut make_ix = env.make_ix(1, None, None);
+        make_ix.accounts.swap(7, 8);
+        let maker = env.maker.insecure_clone();
+        assert!(env.send(make_ix, &maker).is_err());
+    }
+}
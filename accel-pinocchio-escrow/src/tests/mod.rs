@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod matrix;
+
 #[cfg(test)]
 mod tests {
 
@@ -23,11 +26,31 @@ mod tests {
     const PROGRAM_ID: &str = "4ibrEMW5F6hKnkW4jVedswYv6H6VtwPN6ar6dvXDN1nT";
     const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
     const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+    const SEED: u64 = 123;
 
     fn program_id() -> Pubkey {
         Pubkey::from(crate::ID)
     }
 
+    // Make instruction data, a zero not_before or expires_at leaves the window open
+    fn make_data(
+        bump: u8,
+        seed: u64,
+        amount_to_receive: u64,
+        amount_to_give: u64,
+        not_before: i64,
+        expires_at: i64,
+    ) -> Vec<u8> {
+        escrow_client::MakeArgs {
+            seed,
+            amount_to_receive,
+            amount_to_give,
+            not_before: Some(not_before).filter(|t| *t != 0),
+            expires_at: Some(expires_at).filter(|t| *t != 0),
+        }
+        .data(bump)
+    }
+
     fn setup() -> (
         LiteSVM,
         Keypair,
@@ -117,7 +140,7 @@ mod tests {
 
         // Derive the PDA for the escrow account using the maker's public key and a seed value
         let escrow = Pubkey::find_program_address(
-            &[b"escrow".as_ref(), maker.as_ref()],
+            &[b"escrow".as_ref(), maker.as_ref(), &SEED.to_le_bytes()],
             &PROGRAM_ID.parse().unwrap(),
         );
         msg!("Escrow PDA: {}\n", escrow.0);
@@ -191,13 +214,7 @@ mod tests {
         msg!("Bump: {}", bump);
 
         // Create the "Make" instruction to deposit tokens into the escrow
-        let make_data = [
-            vec![0u8], // Discriminator for "Make" instruction
-            bump.to_le_bytes().to_vec(),
-            amount_to_receive.to_le_bytes().to_vec(),
-            amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
+        let make_data = make_data(bump, SEED, amount_to_receive, amount_to_give, 0, 0);
         let make_ix = Instruction {
             program_id: program_id,
             accounts: vec![
@@ -209,6 +226,7 @@ mod tests {
                 AccountMeta::new(vault, false),
                 AccountMeta::new(system_program, false),
                 AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
                 AccountMeta::new(asspciated_token_program, false),
                 AccountMeta::new(Rent::id(), false),
             ],
@@ -228,6 +246,28 @@ mod tests {
         msg!("\n\nMake transaction sucessfull");
         msg!("CUs Consumed: {}", tx.compute_units_consumed);
 
+        // The escrow is stored with its discriminator and layout version up front
+        let escrow_account = svm.get_account(&escrow).unwrap();
+        assert_eq!(escrow_account.owner, program_id);
+        assert_eq!(escrow_account.data.len(), crate::state::Escrow::LEN);
+        assert_eq!(escrow_account.data[0], crate::state::Escrow::DISCRIMINATOR);
+        assert_eq!(escrow_account.data[1], crate::state::Escrow::VERSION);
+        assert_eq!(&escrow_account.data[2..10], &SEED.to_le_bytes());
+        assert_eq!(&escrow_account.data[10..42], maker.as_ref());
+        assert_eq!(escrow_account.data[crate::state::Escrow::LEN - 1], bump);
+
+        // The client decodes the same layout
+        assert_eq!(escrow_client::Escrow::LEN, crate::state::Escrow::LEN);
+        let escrow_data = escrow_client::Escrow::try_from_bytes(&escrow_account.data).unwrap();
+        assert_eq!(escrow_data.seed, SEED);
+        assert_eq!(escrow_data.maker, maker);
+        assert_eq!(escrow_data.mint_a, mint_a);
+        assert_eq!(escrow_data.mint_b, mint_b);
+        assert_eq!(escrow_data.amount_to_receive, amount_to_receive);
+        assert_eq!(escrow_data.amount_to_give, amount_to_give);
+        assert_eq!(escrow_data.not_before, None);
+        assert_eq!(escrow_data.expires_at, None);
+
         // // Verify the vault account and escrow account data after the "Make" instruction
         // let vault_account = svm.get_account(&escrow).unwrap();
         // let vault_data = spl_token::state::Account::try_from(&vault_account.data).unwrap();
@@ -280,13 +320,7 @@ mod tests {
         msg!("Bump: {}", bump);
 
         // Create the "Make" instruction to deposit tokens into the escrow
-        let make_data = [
-            vec![0u8], // Discriminator for "Make" instruction
-            bump.to_le_bytes().to_vec(),
-            amount_to_receive.to_le_bytes().to_vec(),
-            amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
+        let make_data = make_data(bump, SEED, amount_to_receive, amount_to_give, 0, 0);
         let make_ix = Instruction {
             program_id: program_id,
             accounts: vec![
@@ -298,6 +332,7 @@ mod tests {
                 AccountMeta::new(vault, false),
                 AccountMeta::new(system_program, false),
                 AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
                 AccountMeta::new(asspciated_token_program, false),
                 AccountMeta::new(Rent::id(), false),
             ],
@@ -342,6 +377,7 @@ mod tests {
                 AccountMeta::new(escrow, false),
                 AccountMeta::new(system_program, false),
                 AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
                 AccountMeta::new(asspciated_token_program, false),
                 AccountMeta::new(Rent::id(), false),
             ],
@@ -361,6 +397,14 @@ mod tests {
         msg!("\n\nTake transaction sucessfull");
         msg!("CUs Consumed: {}", tx2.compute_units_consumed);
 
+        // The vault and the escrow are closed once the swap settles
+        assert!(svm
+            .get_account(&escrow)
+            .is_none_or(|account| account.lamports == 0));
+        assert!(svm
+            .get_account(&vault)
+            .is_none_or(|account| account.lamports == 0));
+
         // // Verify the vault account and escrow account data after the "Make" instruction
         // let vault_account = svm.get_account(&escrow.0).unwrap();
         // let vault_data = spl_token::state::Account::try_from(&vault_account.data).unwrap();
@@ -413,13 +457,7 @@ mod tests {
         msg!("Bump: {}", bump);
 
         // Create the "Make" instruction to deposit tokens into the escrow
-        let make_data = [
-            vec![0u8], // Discriminator for "Make" instruction
-            bump.to_le_bytes().to_vec(),
-            amount_to_receive.to_le_bytes().to_vec(),
-            amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
+        let make_data = make_data(bump, SEED, amount_to_receive, amount_to_give, 0, 0);
         let make_ix = Instruction {
             program_id: program_id,
             accounts: vec![
@@ -431,6 +469,7 @@ mod tests {
                 AccountMeta::new(vault, false),
                 AccountMeta::new(system_program, false),
                 AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
                 AccountMeta::new(asspciated_token_program, false),
                 AccountMeta::new(Rent::id(), false),
             ],
@@ -500,4 +539,236 @@ mod tests {
         // assert_eq!(escrow_data.mint_b, mint_b);
         // assert_eq!(escrow_data.receive, 10);
     }
+
+    #[test]
+    pub fn test_make_rejects_malformed_data() {
+        let (
+            mut svm,
+            maker_keypair,
+            _taker_keypair,
+            program_id,
+            maker,
+            _taker,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            _maker_ata_b,
+            _taker_ata_a,
+            _taker_ata_b,
+            escrow,
+            escrow_bump,
+            vault,
+            token_program,
+            asspciated_token_program,
+            system_program,
+        ) = setup();
+
+        MintTo::new(&mut svm, &maker_keypair, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        let make_ix = |data: Vec<u8>, vault: Pubkey| Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(maker, true),
+                AccountMeta::new(mint_a, false),
+                AccountMeta::new(mint_b, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(maker_ata_a, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(system_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(asspciated_token_program, false),
+                AccountMeta::new(Rent::id(), false),
+            ],
+            data,
+        };
+
+        let amount: u64 = 100000000;
+
+        // amount_to_give is missing
+        let mut truncated_data = make_data(escrow_bump, SEED, amount, amount, 0, 0);
+        truncated_data.truncate(truncated_data.len() - 8);
+
+        // a zero amount is not a valid offer
+        let zero_amount_data = make_data(escrow_bump, SEED, amount, 0, 0, 0);
+
+        // the bump has to match the escrow address
+        let wrong_bump_data = make_data(escrow_bump.wrapping_sub(1), SEED, amount, amount, 0, 0);
+
+        // the lock has to end before the expiry
+        let inverted_window_data = make_data(escrow_bump, SEED, amount, amount, 300, 200);
+
+        for data in [
+            truncated_data,
+            zero_amount_data,
+            wrong_bump_data,
+            inverted_window_data,
+        ] {
+            let message = Message::new(&[make_ix(data, vault)], Some(&maker));
+            let recent_blockhash = svm.latest_blockhash();
+            let transaction = Transaction::new(&[&maker_keypair], message, recent_blockhash);
+            assert!(svm.send_transaction(transaction).is_err());
+        }
+
+        // the vault has to be the escrow ATA for mint A
+        let valid_data = make_data(escrow_bump, SEED, amount, amount, 0, 0);
+        let message = Message::new(&[make_ix(valid_data, maker_ata_a)], Some(&maker));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&maker_keypair], message, recent_blockhash);
+        assert!(svm.send_transaction(transaction).is_err());
+
+        assert!(svm.get_account(&escrow).is_none());
+    }
+
+    #[test]
+    pub fn test_take_token_2022() {
+        let mut svm = LiteSVM::new();
+        let maker_keypair = Keypair::new();
+        let maker = maker_keypair.pubkey();
+        let taker_keypair = Keypair::new();
+        let taker = taker_keypair.pubkey();
+
+        svm.airdrop(&maker, 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&taker, 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+
+        let so_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/deploy/escrow.so");
+        let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
+        svm.add_program(program_id(), &program_data);
+
+        let program_id = program_id();
+        let token_program = spl_token_2022::ID;
+        let asspciated_token_program = ASSOCIATED_TOKEN_PROGRAM_ID.parse::<Pubkey>().unwrap();
+        let system_program = solana_sdk_ids::system_program::ID;
+
+        // the two mints use different decimals so a mixed up TransferChecked would fail
+        let mint_a = CreateMint::new(&mut svm, &maker_keypair)
+            .decimals(6)
+            .authority(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let mint_b = CreateMint::new(&mut svm, &taker_keypair)
+            .decimals(9)
+            .authority(&taker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &maker_keypair, &mint_a)
+            .owner(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let maker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &maker_keypair, &mint_b)
+            .owner(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let taker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &taker_keypair, &mint_a)
+            .owner(&taker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let taker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &taker_keypair, &mint_b)
+            .owner(&taker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        MintTo::new(&mut svm, &maker_keypair, &mint_a, &maker_ata_a, 1000000000)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &taker_keypair, &mint_b, &taker_ata_b, 1000000000)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow".as_ref(), maker.as_ref(), &SEED.to_le_bytes()],
+            &program_id,
+        );
+        let vault = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &escrow,
+            &mint_a,
+            &token_program,
+        );
+
+        let amount_to_receive: u64 = 100000000;
+        let amount_to_give: u64 = 500000000;
+
+        let make_ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(maker, true),
+                AccountMeta::new(mint_a, false),
+                AccountMeta::new(mint_b, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(maker_ata_a, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(system_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(asspciated_token_program, false),
+                AccountMeta::new(Rent::id(), false),
+            ],
+            data: make_data(bump, SEED, amount_to_receive, amount_to_give, 0, 0),
+        };
+        let message = Message::new(&[make_ix], Some(&maker));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&maker_keypair], message, recent_blockhash);
+        svm.send_transaction(transaction).unwrap();
+
+        // the vault is a Token-2022 account
+        assert_eq!(svm.get_account(&vault).unwrap().owner, token_program);
+        assert_eq!(token_2022_amount(&svm, &vault), amount_to_give);
+
+        let take_ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(taker, true),
+                AccountMeta::new(maker, false),
+                AccountMeta::new(mint_a, false),
+                AccountMeta::new(mint_b, false),
+                AccountMeta::new(taker_ata_a, false),
+                AccountMeta::new(taker_ata_b, false),
+                AccountMeta::new(maker_ata_b, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(system_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(token_program, false),
+                AccountMeta::new(asspciated_token_program, false),
+                AccountMeta::new(Rent::id(), false),
+            ],
+            data: vec![1u8, bump],
+        };
+        let message = Message::new(&[take_ix], Some(&taker));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&taker_keypair], message, recent_blockhash);
+        svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_2022_amount(&svm, &taker_ata_a), amount_to_give);
+        assert_eq!(token_2022_amount(&svm, &maker_ata_b), amount_to_receive);
+        assert!(svm
+            .get_account(&vault)
+            .is_none_or(|account| account.lamports == 0));
+        assert!(svm
+            .get_account(&escrow)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    fn token_2022_amount(svm: &LiteSVM, account: &Pubkey) -> u64 {
+        use spl_token_2022::{extension::StateWithExtensions, state::Account};
+
+        let account = svm.get_account(account).unwrap();
+        StateWithExtensions::<Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
 }
@@ -2,3 +2,14 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+#[constant]
+pub const FEE_BASIS_POINTS: u16 = 10_000;
+
+// team fee is charged on every trade, capped at 20%
+#[constant]
+pub const MAX_TEAM_FEE: u16 = 2_000;
+
+// listing fee is charged on the raised SOL when listing on raydium, capped at 10%
+#[constant]
+pub const MAX_LISTING_FEE: u16 = 1_000;
@@ -44,4 +44,8 @@ pub enum CoopMemeError {
     TokenNotListed,
     #[msg("Listing info not valid")]
     InvalidListingInfo,
+    #[msg("Fee exceeds the allowed basis points")]
+    InvalidFee,
+    #[msg("Owner and affiliate fee shares exceed the team fee")]
+    InvalidFeeSplit,
 }
@@ -145,6 +145,8 @@ impl<'info> UpdateConfig<'info> {
             self.config.init_virtual_token = token;
         }
 
+        self.config.fee_rates(None)?;
+
         Ok(())
     }
 
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    error::*,
+    state::{ConfigData, FeeOverride, FeeRates},
+};
+
+#[derive(Accounts)]
+pub struct SetFeeOverride<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        has_one = admin @ CoopMemeError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ConfigData>>,
+    pub coop_token: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      space = 8 + FeeOverride::INIT_SPACE,
+      payer = admin,
+      seeds = [b"fee_override", coop_token.key().as_ref()],
+      bump
+    ]]
+    pub fee_override: Box<Account<'info, FeeOverride>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetFeeOverride<'info> {
+    pub fn set_fee_override(
+        &mut self,
+        bumps: &SetFeeOverrideBumps,
+        team_fee: u16,
+        owner_fee: u16,
+        affiliated_fee: u16,
+        listing_fee: u16,
+    ) -> Result<()> {
+        FeeRates {
+            team_fee,
+            owner_fee,
+            affiliated_fee,
+            listing_fee,
+        }
+        .validate()?;
+
+        self.fee_override.set_inner(FeeOverride {
+            coop_token: self.coop_token.key(),
+            team_fee,
+            owner_fee,
+            affiliated_fee,
+            listing_fee,
+            bump: bumps.fee_override,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeOverride<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        has_one = admin @ CoopMemeError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ConfigData>>,
+    pub coop_token: Box<Account<'info, Mint>>,
+    #[account[
+      mut,
+      close = admin,
+      seeds = [b"fee_override", coop_token.key().as_ref()],
+      bump = fee_override.bump
+    ]]
+    pub fee_override: Box<Account<'info, FeeOverride>>,
+}
+
+impl<'info> RemoveFeeOverride<'info> {
+    pub fn remove_fee_override(&mut self) -> Result<()> {
+        // account is closed by anchor, trades fall back to the config fees
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 pub mod config;
 
 pub use config::*;
+
+pub mod fee_override;
+
+pub use fee_override::*;
@@ -1,4 +1,4 @@
-use crate::state::{ConfigData, MemeCoinData};
+use crate::state::{ConfigData, FeeOverride, MemeCoinData};
 use crate::{
     error::*,
     events::{BurnEvent, ListEvent, TradingOverEvent},
@@ -74,6 +74,12 @@ pub struct List<'info> {
       bump = memecoin.memecoin_bump
     ]]
     pub memecoin: Box<Account<'info, MemeCoinData>>,
+    /// CHECK: may be uninitialized, read through FeeOverride::load
+    #[account[
+      seeds = [b"fee_override", coop_token.key().as_ref()],
+      bump
+    ]]
+    pub fee_override: UncheckedAccount<'info>,
     #[account(
       mut,
       associated_token::mint = coop_token,
@@ -202,10 +208,13 @@ impl<'info> List<'info> {
         let mut init_token_0;
         let mut init_token_1;
 
+        let fees = self
+            .config
+            .fee_rates(FeeOverride::load(&self.fee_override)?.as_ref())?;
         let listing_fee = self
             .memecoin
             .real_sol_reserves
-            .checked_mul(self.config.listing_fee as u64)
+            .checked_mul(fees.listing_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -1,7 +1,7 @@
 use crate::{
     error::*,
     events::{BondingCurveStartedEvent, TradeEvent, TradingOverEvent},
-    state::{ConfigData, MemeCoinData},
+    state::{ConfigData, FeeOverride, MemeCoinData},
     utils::*,
 };
 use anchor_lang::prelude::*;
@@ -59,6 +59,12 @@ pub struct Trade<'info> {
       bump = memecoin.memecoin_bump
     ]]
     pub memecoin: Box<Account<'info, MemeCoinData>>,
+    /// CHECK: may be uninitialized, read through FeeOverride::load
+    #[account[
+      seeds = [b"fee_override", coop_token.key().as_ref()],
+      bump
+    ]]
+    pub fee_override: UncheckedAccount<'info>,
     #[account(
       mut,
       associated_token::mint = coop_token,
@@ -311,9 +317,12 @@ impl<'info> Trade<'info> {
     }
 
     fn _calculate_and_send_fees(&self, amount: u64) -> Result<(Option<((u64))>)> {
+        let fees = self
+            .config
+            .fee_rates(FeeOverride::load(&self.fee_override)?.as_ref())?;
         // let team_fees = amount *  / 10000;
         let team_fees = amount
-            .checked_mul(self.config.team_fee as u64)
+            .checked_mul(fees.team_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -321,7 +330,7 @@ impl<'info> Trade<'info> {
             .unwrap();
         // let owner_fees = team_fees * self.config.owner_fee as u64 / 10000;
         let owner_fees = team_fees
-            .checked_mul(self.config.owner_fee as u64)
+            .checked_mul(fees.owner_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -329,7 +338,7 @@ impl<'info> Trade<'info> {
             .unwrap();
         // let affiliate_fees = team_fees * self.config.affiliated_fee as u64 / 10000;
         let affiliate_fees = team_fees
-            .checked_mul(self.config.affiliated_fee as u64)
+            .checked_mul(fees.affiliated_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -380,10 +389,13 @@ impl<'info> Trade<'info> {
         // let team_fees = amount * self.config.team_fee as u64 / 10000;
         // let owner_fees = team_fees * self.config.owner_fee as u64 / 10000;
         // let affiliate_fees = team_fees * self.config.affiliated_fee as u64 / 10000;
+        let fees = self
+            .config
+            .fee_rates(FeeOverride::load(&self.fee_override)?.as_ref())?;
 
         // let team_fees = amount *  / 10000;
         let team_fees = amount
-            .checked_mul(self.config.team_fee as u64)
+            .checked_mul(fees.team_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -391,7 +403,7 @@ impl<'info> Trade<'info> {
             .unwrap();
         // let owner_fees = team_fees * self.config.owner_fee as u64 / 10000;
         let owner_fees = team_fees
-            .checked_mul(self.config.owner_fee as u64)
+            .checked_mul(fees.owner_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
@@ -399,7 +411,7 @@ impl<'info> Trade<'info> {
             .unwrap();
         // let affiliate_fees = team_fees * self.config.affiliated_fee as u64 / 10000;
         let affiliate_fees = team_fees
-            .checked_mul(self.config.affiliated_fee as u64)
+            .checked_mul(fees.affiliated_fee as u64)
             .ok_or(CoopMemeError::InvalidOperation)
             .unwrap()
             .checked_div(10000)
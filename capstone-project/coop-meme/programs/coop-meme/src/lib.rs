@@ -54,6 +54,28 @@ pub mod coop_meme {
         )
     }
 
+    pub fn set_fee_override(
+        // only admin
+        ctx: Context<SetFeeOverride>,
+        team_fee: u16,
+        owner_fee: u16,
+        affiliated_fee: u16,
+        listing_fee: u16,
+    ) -> Result<()> {
+        ctx.accounts.set_fee_override(
+            &ctx.bumps,
+            team_fee,
+            owner_fee,
+            affiliated_fee,
+            listing_fee,
+        )
+    }
+
+    pub fn remove_fee_override(ctx: Context<RemoveFeeOverride>) -> Result<()> {
+        // only admin
+        ctx.accounts.remove_fee_override()
+    }
+
     pub fn create_token(
         // only admin can call
         ctx: Context<MemeCoin>,
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::{constants::*, error::CoopMemeError, state::FeeOverride};
+
 #[account]
 #[derive(InitSpace)]
 pub struct ConfigData {
@@ -21,6 +23,62 @@ pub struct ConfigData {
     pub global_vault_bump: u8,
 }
 
+impl ConfigData {
+    // returns the fees for a memecoin, using its override if one is set
+    pub fn fee_rates(&self, fee_override: Option<&FeeOverride>) -> Result<FeeRates> {
+        let rates = match fee_override {
+            Some(fee_override) => FeeRates {
+                team_fee: fee_override.team_fee,
+                owner_fee: fee_override.owner_fee,
+                affiliated_fee: fee_override.affiliated_fee,
+                listing_fee: fee_override.listing_fee,
+            },
+            None => FeeRates {
+                team_fee: self.team_fee,
+                owner_fee: self.owner_fee,
+                affiliated_fee: self.affiliated_fee,
+                listing_fee: self.listing_fee,
+            },
+        };
+        rates.validate()?;
+
+        Ok(rates)
+    }
+}
+
+// team_fee and listing_fee are bps of the traded/raised SOL,
+// owner_fee and affiliated_fee are bps of the team fee
+#[derive(Clone, Copy)]
+pub struct FeeRates {
+    pub team_fee: u16,
+    pub owner_fee: u16,
+    pub affiliated_fee: u16,
+    pub listing_fee: u16,
+}
+
+impl FeeRates {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.team_fee <= FEE_BASIS_POINTS
+                && self.owner_fee <= FEE_BASIS_POINTS
+                && self.affiliated_fee <= FEE_BASIS_POINTS
+                && self.listing_fee <= FEE_BASIS_POINTS,
+            CoopMemeError::InvalidFee
+        );
+        require!(self.team_fee <= MAX_TEAM_FEE, CoopMemeError::InvalidFee);
+        require!(
+            self.listing_fee <= MAX_LISTING_FEE,
+            CoopMemeError::InvalidFee
+        );
+        require!(
+            (self.owner_fee as u32) + (self.affiliated_fee as u32) <= FEE_BASIS_POINTS as u32,
+            CoopMemeError::InvalidFeeSplit
+        );
+
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct GlobalVault {}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+// per memecoin fees which take precedence over the global config fees
+#[account]
+#[derive(InitSpace)]
+pub struct FeeOverride {
+    pub coop_token: Pubkey,
+    pub team_fee: u16,
+    pub owner_fee: u16,
+    pub affiliated_fee: u16,
+    pub listing_fee: u16,
+    pub bump: u8,
+}
+
+impl FeeOverride {
+    // The override PDA is always passed so callers can't leave it out to dodge it,
+    // an address the program hasn't initialized (or has closed) means no override
+    pub fn load(info: &AccountInfo) -> Result<Option<FeeOverride>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(FeeOverride::try_deserialize(&mut &data[..])?))
+    }
+}
@@ -9,3 +9,7 @@ pub use memecoin::*;
 pub mod votes;
 
 pub use votes::*;
+
+pub mod fee_override;
+
+pub use fee_override::*;
@@ -39,6 +39,13 @@ describe('coop-meme-2', () => {
     'G11FKBRaAkHAKuLCgLM6K6NUc9rTjPAznRCjZifrTQe2'
   );
 
+  // trades and listings always take the override PDA, set or not
+  const feeOverridePda = (coopToken: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from('fee_override'), coopToken.toBuffer()],
+      program.programId
+    )[0];
+
   it('Is initialized!', async () => {
     // Add your test here.
 
@@ -137,10 +144,161 @@ describe('coop-meme-2', () => {
     );
   });
 
+  it('rejects out of bound fees', async () => {
+    const owner = provider.wallet.publicKey;
+
+    const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .updateConfig(
+          10001,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          owner,
+          config: configPda,
+        })
+        .rpc();
+      assert.fail('team fee above 10000 bps should be rejected');
+    } catch (err) {
+      assert.include(err.toString(), 'InvalidFee');
+    }
+
+    try {
+      await program.methods
+        .updateConfig(
+          null,
+          6000,
+          6000,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          owner,
+          config: configPda,
+        })
+        .rpc();
+      assert.fail('owner + affiliate fee above 10000 bps should be rejected');
+    } catch (err) {
+      assert.include(err.toString(), 'InvalidFeeSplit');
+    }
+
+    const config = await program.account.configData.fetch(configPda);
+    assert.strictEqual(config.teamFee, 1000);
+    assert.strictEqual(config.ownerFee, 1000);
+    assert.strictEqual(config.affiliatedFee, 1000);
+  });
+
   it('Is creating memecoin!', async () => {
     await create_tokens();
   });
 
+  it('sets and removes a fee override', async () => {
+    const admin = provider.wallet.publicKey;
+    const creator = provider.wallet.publicKey;
+
+    const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('config')],
+      program.programId
+    );
+
+    const config = await program.account.configData.fetch(configPda);
+
+    const seedBuffer = new BN(config.totalCoopCreated).toArrayLike(
+      Buffer,
+      'le',
+      4
+    );
+
+    const [coopToken] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('mint'), creator.toBuffer(), seedBuffer],
+      program.programId
+    );
+
+    const [memecoinPda] =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from('memecoin'), coopToken.toBuffer()],
+        program.programId
+      );
+
+    const [feeOverride] =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from('fee_override'), coopToken.toBuffer()],
+        program.programId
+      );
+
+    try {
+      await program.methods
+        .setFeeOverride(5000, 0, 0, 0)
+        .accounts({
+          admin,
+          config: configPda,
+          coopToken,
+          feeOverride,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail('override above max team fee should be rejected');
+    } catch (err) {
+      assert.include(err.toString(), 'InvalidFee');
+    }
+
+    await program.methods
+      .setFeeOverride(500, 0, 0, 250)
+      .accounts({
+        admin,
+        config: configPda,
+        coopToken,
+        feeOverride,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const feeOverrideState = await program.account.feeOverride.fetch(
+      feeOverride
+    );
+    assert.strictEqual(
+      feeOverrideState.coopToken.toString(),
+      coopToken.toString()
+    );
+    assert.strictEqual(feeOverrideState.teamFee, 500);
+    assert.strictEqual(feeOverrideState.ownerFee, 0);
+    assert.strictEqual(feeOverrideState.affiliatedFee, 0);
+    assert.strictEqual(feeOverrideState.listingFee, 250);
+
+    await program.methods
+      .removeFeeOverride()
+      .accounts({
+        admin,
+        config: configPda,
+        coopToken,
+        feeOverride,
+      })
+      .rpc();
+
+    const closed = await provider.connection.getAccountInfo(feeOverride);
+    assert.isNull(closed);
+  });
+
   it('first buying memecoin!', async () => {
     const trader = provider.wallet.publicKey;
     const creator = provider.wallet.publicKey;
@@ -222,6 +380,7 @@ describe('coop-meme-2', () => {
         globalVault,
         coopToken,
         memecoin: memecoinPda,
+        feeOverride: feeOverridePda(coopToken),
         globalTokenAta,
         traderTokenAta,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -1070,6 +1229,7 @@ describe('coop-meme-2', () => {
         globalVault,
         coopToken,
         memecoin: memecoinPda,
+        feeOverride: feeOverridePda(coopToken),
         globalTokenAta,
         traderTokenAta,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -1329,6 +1489,7 @@ describe('coop-meme-2', () => {
         globalVault,
         coopToken,
         memecoin: memecoinPda,
+        feeOverride: feeOverridePda(coopToken),
         globalTokenAta,
         traderTokenAta,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -1904,6 +2065,7 @@ describe('coop-meme-2', () => {
         token1Mint,
         coopToken, // fine
         memecoin: memecoinPda, // fine
+        feeOverride: feeOverridePda(coopToken),
         // globalWsolAccount,
         globalTokenAta, // fine
         ownerToken0, // fine
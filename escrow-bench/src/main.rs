@@ -0,0 +1,98 @@
+// Benchmarks the pinocchio escrow (`Make`/`Take`/`Cancel`) against the Anchor escrow
+// (`make`/`take`/`refund`) in LiteSVM and checks the results against `budgets.json`.
+//
+// Build both programs first (`cargo build-sbf` in `accel-pinocchio-escrow`, `anchor build` in
+// `escrow-litesvm`), then run `cargo run --release [-- --budgets <file>] [--out <dir>]`.
+
+mod programs;
+mod report;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use programs::{Cost, Env, Implementation};
+use report::{Budgets, Measurement, Report};
+
+fn measure(implementation: Implementation) -> Result<Vec<Measurement>, String> {
+    let mut env = Env::new(implementation)?;
+    let measurement = |instruction, cost: Cost, rent_lamports| Measurement {
+        implementation,
+        instruction,
+        compute_units: cost.compute_units,
+        transaction_size: cost.transaction_size,
+        rent_lamports,
+    };
+
+    let make = env.make(1)?;
+    let locked = env.escrow_rent(1);
+    let take = env.take(1)?;
+
+    // a second escrow is cancelled instead of taken
+    env.make(2)?;
+    let refunded = env.escrow_rent(2);
+    let cancel = env.cancel(2)?;
+
+    Ok(vec![
+        measurement("make", make, locked),
+        measurement("take", take, locked),
+        measurement("cancel", cancel, refunded),
+    ])
+}
+
+fn main() -> ExitCode {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut budgets_path = manifest_dir.join("budgets.json");
+    let mut out_dir = manifest_dir.join("target/bench");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--budgets", Some(path)) => budgets_path = PathBuf::from(path),
+            ("--out", Some(path)) => out_dir = PathBuf::from(path),
+            _ => {
+                eprintln!("usage: escrow-bench [--budgets <file>] [--out <dir>]");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let budgets: Budgets = match std::fs::read_to_string(&budgets_path)
+        .map_err(|e| e.to_string())
+        .and_then(|budgets| serde_json::from_str(&budgets).map_err(|e| e.to_string()))
+    {
+        Ok(budgets) => budgets,
+        Err(e) => {
+            eprintln!("failed to load {}: {e}", budgets_path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut measurements = Vec::new();
+    for implementation in Implementation::ALL {
+        match measure(implementation) {
+            Ok(results) => measurements.extend(results),
+            Err(e) => {
+                eprintln!("{} failed: {e}", implementation.name());
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let report = Report::new(measurements, &budgets);
+    let markdown = report.to_markdown();
+    println!("{markdown}");
+
+    if let Err(e) = std::fs::create_dir_all(&out_dir)
+        .and_then(|_| std::fs::write(out_dir.join("report.md"), &markdown))
+        .and_then(|_| std::fs::write(out_dir.join("report.json"), report.to_json()))
+    {
+        eprintln!("failed to write the report to {}: {e}", out_dir.display());
+        return ExitCode::FAILURE;
+    }
+
+    if report.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,337 @@
+// Builds the pinocchio instructions with `escrow-client` and the Anchor ones from raw bytes, so
+// the bench does not depend on either program crate.
+
+use std::path::PathBuf;
+
+use escrow_client::{CancelArgs, MakeArgs, TakeArgs};
+use litesvm::LiteSVM;
+use litesvm_token::{
+    spl_token::{self, solana_program::hash::hash},
+    CreateAssociatedTokenAccount, CreateMint, MintTo,
+};
+use serde::Serialize;
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_message::Message;
+use solana_native_token::LAMPORTS_PER_SOL;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+const ANCHOR_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk_ids::system_program::ID;
+
+const AMOUNT_TO_RECEIVE: u64 = 100000000;
+const AMOUNT_TO_GIVE: u64 = 500000000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Implementation {
+    Pinocchio,
+    Anchor,
+}
+
+impl Implementation {
+    pub const ALL: [Implementation; 2] = [Implementation::Pinocchio, Implementation::Anchor];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Implementation::Pinocchio => "pinocchio",
+            Implementation::Anchor => "anchor",
+        }
+    }
+
+    fn program_id(&self) -> Pubkey {
+        match self {
+            Implementation::Pinocchio => escrow_client::ID,
+            Implementation::Anchor => ANCHOR_PROGRAM_ID,
+        }
+    }
+
+    pub fn so_path(&self) -> PathBuf {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        match self {
+            Implementation::Pinocchio => {
+                manifest_dir.join("../accel-pinocchio-escrow/target/deploy/escrow.so")
+            }
+            Implementation::Anchor => {
+                manifest_dir.join("../escrow-litesvm/target/deploy/anchor_escrow.so")
+            }
+        }
+    }
+}
+
+// Anchor instruction data starts with the first 8 bytes of sha256("global:<name>")
+fn anchor_discriminator(name: &str) -> Vec<u8> {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec()
+}
+
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// What a single successful transaction cost
+pub struct Cost {
+    pub compute_units: u64,
+    pub transaction_size: usize,
+}
+
+pub struct Env {
+    pub svm: LiteSVM,
+    implementation: Implementation,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    maker_ata_a: Pubkey,
+    maker_ata_b: Pubkey,
+    taker_ata_a: Pubkey,
+    taker_ata_b: Pubkey,
+}
+
+impl Env {
+    pub fn new(implementation: Implementation) -> Result<Self, String> {
+        let mut svm = LiteSVM::new();
+        let maker = Keypair::new();
+        let taker = Keypair::new();
+
+        svm.airdrop(&maker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .map_err(|e| format!("airdrop failed: {:?}", e.err))?;
+        svm.airdrop(&taker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .map_err(|e| format!("airdrop failed: {:?}", e.err))?;
+
+        let so_path = implementation.so_path();
+        let program_data = std::fs::read(&so_path)
+            .map_err(|e| format!("failed to read {}: {e}", so_path.display()))?;
+        svm.add_program(implementation.program_id(), &program_data);
+
+        let mint_a = CreateMint::new(&mut svm, &maker)
+            .decimals(6)
+            .authority(&maker.pubkey())
+            .send()
+            .unwrap();
+        let mint_b = CreateMint::new(&mut svm, &taker)
+            .decimals(6)
+            .authority(&taker.pubkey())
+            .send()
+            .unwrap();
+
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_a)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let maker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint_b)
+            .owner(&maker.pubkey())
+            .send()
+            .unwrap();
+        let taker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &taker, &mint_a)
+            .owner(&taker.pubkey())
+            .send()
+            .unwrap();
+        let taker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &taker, &mint_b)
+            .owner(&taker.pubkey())
+            .send()
+            .unwrap();
+
+        MintTo::new(&mut svm, &maker, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &taker, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
+
+        Ok(Self {
+            svm,
+            implementation,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        })
+    }
+
+    fn escrow(&self, seed: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"escrow".as_ref(),
+                self.maker.pubkey().as_ref(),
+                &seed.to_le_bytes(),
+            ],
+            &self.implementation.program_id(),
+        )
+    }
+
+    fn vault(&self, seed: u64) -> Pubkey {
+        associated_token_address(&self.escrow(seed).0, &self.mint_a)
+    }
+
+    // Lamports held by the escrow state and its vault, i.e. the rent `make` locks up
+    pub fn escrow_rent(&self, seed: u64) -> u64 {
+        [self.escrow(seed).0, self.vault(seed)]
+            .iter()
+            .filter_map(|account| self.svm.get_account(account))
+            .map(|account| account.lamports)
+            .sum()
+    }
+
+    fn make_ix(&self, seed: u64) -> Instruction {
+        let escrow = self.escrow(seed).0;
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => escrow_client::make(
+                &maker,
+                &self.mint_a,
+                &self.mint_b,
+                (&TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID),
+                &MakeArgs {
+                    seed,
+                    amount_to_receive: AMOUNT_TO_RECEIVE,
+                    amount_to_give: AMOUNT_TO_GIVE,
+                    not_before: None,
+                    expires_at: None,
+                },
+            ),
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new_readonly(self.mint_a, false),
+                    AccountMeta::new_readonly(self.mint_b, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data: [
+                    anchor_discriminator("make"),
+                    seed.to_le_bytes().to_vec(),
+                    AMOUNT_TO_GIVE.to_le_bytes().to_vec(),
+                    AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(),
+                ]
+                .concat(),
+            },
+        }
+    }
+
+    fn take_ix(&self, seed: u64) -> Instruction {
+        let escrow = self.escrow(seed).0;
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+        let taker = self.taker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => escrow_client::take(
+                &taker,
+                &maker,
+                &self.mint_a,
+                &self.mint_b,
+                (&TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID),
+                &TakeArgs { seed },
+            ),
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(taker, true),
+                    AccountMeta::new(maker, false),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new_readonly(self.mint_b, false),
+                    AccountMeta::new(self.taker_ata_a, false),
+                    AccountMeta::new(self.taker_ata_b, false),
+                    AccountMeta::new(self.maker_ata_b, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    // no offer book
+                    AccountMeta::new_readonly(ANCHOR_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data: anchor_discriminator("take"),
+            },
+        }
+    }
+
+    // `refund` is the Anchor counterpart of `Cancel`
+    fn cancel_ix(&self, seed: u64) -> Instruction {
+        let escrow = self.escrow(seed).0;
+        let vault = self.vault(seed);
+        let maker = self.maker.pubkey();
+
+        match self.implementation {
+            Implementation::Pinocchio => escrow_client::cancel(
+                &maker,
+                &self.mint_a,
+                &TOKEN_PROGRAM_ID,
+                &CancelArgs { seed },
+            ),
+            Implementation::Anchor => Instruction {
+                program_id: self.implementation.program_id(),
+                accounts: vec![
+                    AccountMeta::new(maker, true),
+                    AccountMeta::new(self.mint_a, false),
+                    AccountMeta::new(self.maker_ata_a, false),
+                    AccountMeta::new(escrow, false),
+                    AccountMeta::new(vault, false),
+                    // no offer book
+                    AccountMeta::new_readonly(ANCHOR_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data: anchor_discriminator("refund"),
+            },
+        }
+    }
+
+    fn send(&mut self, instruction: Instruction, signer: &Keypair) -> Result<Cost, String> {
+        self.svm.expire_blockhash();
+        let message = Message::new(&[instruction], Some(&signer.pubkey()));
+        let recent_blockhash = self.svm.latest_blockhash();
+        let transaction = Transaction::new(&[signer], message, recent_blockhash);
+        let transaction_size = bincode::serialize(&transaction)
+            .map_err(|e| e.to_string())?
+            .len();
+
+        self.svm
+            .send_transaction(transaction)
+            .map(|tx| Cost {
+                compute_units: tx.compute_units_consumed,
+                transaction_size,
+            })
+            .map_err(|e| format!("{:?}\n{}", e.err, e.meta.logs.join("\n")))
+    }
+
+    pub fn make(&mut self, seed: u64) -> Result<Cost, String> {
+        let instruction = self.make_ix(seed);
+        let maker = self.maker.insecure_clone();
+        self.send(instruction, &maker)
+    }
+
+    pub fn take(&mut self, seed: u64) -> Result<Cost, String> {
+        let instruction = self.take_ix(seed);
+        let taker = self.taker.insecure_clone();
+        self.send(instruction, &taker)
+    }
+
+    pub fn cancel(&mut self, seed: u64) -> Result<Cost, String> {
+        let instruction = self.cancel_ix(seed);
+        let maker = self.maker.insecure_clone();
+        self.send(instruction, &maker)
+    }
+}
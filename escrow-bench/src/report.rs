@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::programs::Implementation;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Measurement {
+    pub implementation: Implementation,
+    pub instruction: &'static str,
+    pub compute_units: u64,
+    pub transaction_size: usize,
+    // locked by `make`, returned to the maker by `take` and `cancel`
+    pub rent_lamports: u64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Budget {
+    pub compute_units: u64,
+    pub transaction_size: usize,
+    pub rent_lamports: u64,
+}
+
+// implementation name -> instruction name -> budget
+pub type Budgets = BTreeMap<String, BTreeMap<String, Budget>>;
+
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    pub implementation: Implementation,
+    pub instruction: &'static str,
+    pub metric: &'static str,
+    pub measured: u64,
+    pub budget: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    pub fn new(measurements: Vec<Measurement>, budgets: &Budgets) -> Self {
+        let mut violations = Vec::new();
+
+        for measurement in &measurements {
+            // instructions without a budget are reported but never fail the run
+            let Some(budget) = budgets
+                .get(measurement.implementation.name())
+                .and_then(|budgets| budgets.get(measurement.instruction))
+            else {
+                continue;
+            };
+
+            for (metric, measured, budget) in [
+                (
+                    "compute_units",
+                    measurement.compute_units,
+                    budget.compute_units,
+                ),
+                (
+                    "transaction_size",
+                    measurement.transaction_size as u64,
+                    budget.transaction_size as u64,
+                ),
+                (
+                    "rent_lamports",
+                    measurement.rent_lamports,
+                    budget.rent_lamports,
+                ),
+            ] {
+                if measured > budget {
+                    violations.push(Violation {
+                        implementation: measurement.implementation,
+                        instruction: measurement.instruction,
+                        metric,
+                        measured,
+                        budget,
+                    });
+                }
+            }
+        }
+
+        Self {
+            measurements,
+            violations,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    fn find(&self, implementation: Implementation, instruction: &str) -> Option<&Measurement> {
+        self.measurements
+            .iter()
+            .find(|m| m.implementation == implementation && m.instruction == instruction)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report is serializable")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Escrow benchmark\n\n");
+
+        out.push_str("| instruction | implementation | compute units | tx size (bytes) | rent (lamports) |\n");
+        out.push_str("|---|---|---:|---:|---:|\n");
+        for m in &self.measurements {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                m.instruction,
+                m.implementation.name(),
+                m.compute_units,
+                m.transaction_size,
+                m.rent_lamports
+            ));
+        }
+
+        out.push_str("\n## Pinocchio vs Anchor\n\n");
+        out.push_str("| instruction | compute units saved | bytes saved |\n");
+        out.push_str("|---|---:|---:|\n");
+        for m in self
+            .measurements
+            .iter()
+            .filter(|m| m.implementation == Implementation::Pinocchio)
+        {
+            let Some(anchor) = self.find(Implementation::Anchor, m.instruction) else {
+                continue;
+            };
+            out.push_str(&format!(
+                "| {} | {} ({:.1}%) | {} |\n",
+                m.instruction,
+                anchor.compute_units as i64 - m.compute_units as i64,
+                percent_saved(m.compute_units, anchor.compute_units),
+                anchor.transaction_size as i64 - m.transaction_size as i64
+            ));
+        }
+
+        out.push_str("\n## Budgets\n\n");
+        if self.violations.is_empty() {
+            out.push_str("All measurements are within budget.\n");
+        } else {
+            for v in &self.violations {
+                out.push_str(&format!(
+                    "- {} `{}` {}: {} > {}\n",
+                    v.implementation.name(),
+                    v.instruction,
+                    v.metric,
+                    v.measured,
+                    v.budget
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+fn percent_saved(pinocchio: u64, anchor: u64) -> f64 {
+    if anchor == 0 {
+        return 0.0;
+    }
+    (anchor as f64 - pinocchio as f64) * 100.0 / anchor as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(implementation: Implementation, compute_units: u64) -> Measurement {
+        Measurement {
+            implementation,
+            instruction: "make",
+            compute_units,
+            transaction_size: 400,
+            rent_lamports: 3000000,
+        }
+    }
+
+    fn budgets() -> Budgets {
+        serde_json::from_str(
+            r#"{ "pinocchio": { "make": { "compute_units": 20000, "transaction_size": 500, "rent_lamports": 4000000 } } }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let report = Report::new(
+            vec![
+                measurement(Implementation::Pinocchio, 15000),
+                // no anchor budget, so this is only reported
+                measurement(Implementation::Anchor, 50000),
+            ],
+            &budgets(),
+        );
+
+        assert!(report.passed());
+        assert!(report
+            .to_markdown()
+            .contains("| make | 35000 (70.0%) | 0 |"));
+    }
+
+    #[test]
+    fn test_over_budget() {
+        let report = Report::new(
+            vec![measurement(Implementation::Pinocchio, 25000)],
+            &budgets(),
+        );
+
+        assert!(!report.passed());
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].metric, "compute_units");
+        assert!(report.to_json().contains("\"measured\": 25000"));
+    }
+}
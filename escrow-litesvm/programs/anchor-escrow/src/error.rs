@@ -6,4 +6,36 @@ pub enum EscrowError {
     NeedToWait,
     #[msg("Amount is too big")]
     AmountTooBig,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Escrow is reserved for another taker")]
+    UnauthorizedTaker,
+    #[msg("Escrow has expired")]
+    Expired,
+    #[msg("Escrow has not expired yet")]
+    NotExpired,
+    #[msg("Escrow time window is invalid")]
+    InvalidWindow,
+    #[msg("Received less than the asked amount")]
+    ReceivedTooLittle,
+    #[msg("Basket holds too many items")]
+    BasketFull,
+    #[msg("Basket has nothing to swap")]
+    EmptyBasket,
+    #[msg("Basket accounts do not match the escrow")]
+    InvalidBasketAccounts,
+    #[msg("Offer book is full")]
+    OfferBookFull,
+    #[msg("Listed escrow requires its offer book")]
+    OfferBookMissing,
+    #[msg("Escrow is already listed")]
+    AlreadyListed,
+    #[msg("Escrow is not listed")]
+    NotListed,
+    #[msg("Account is not a legacy escrow")]
+    UnknownLayout,
+    #[msg("Offer is too small to be listed")]
+    OfferTooSmall,
+    #[msg("Escrow terms changed since the taker read them")]
+    TermsChanged,
 }
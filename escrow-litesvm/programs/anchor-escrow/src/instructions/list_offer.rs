@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+use crate::state::{Escrow, Offer, OfferBook};
+
+#[derive(Accounts)]
+pub struct ListOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump,
+        space = 8 + OfferBook::INIT_SPACE,
+    )]
+    pub offer_book: Account<'info, OfferBook>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ListOffer<'info> {
+    pub fn list(&mut self, bumps: &ListOfferBumps) -> Result<()> {
+        require!(!self.escrow.listed, EscrowError::AlreadyListed);
+
+        if self.offer_book.mint_a == Pubkey::default() {
+            self.offer_book.mint_a = self.escrow.mint_a;
+            self.offer_book.mint_b = self.escrow.mint_b;
+            self.offer_book.bump = bumps.offer_book;
+        }
+
+        self.offer_book.insert(
+            Offer {
+                escrow: self.escrow.key(),
+                deposit: self.escrow.deposit,
+                receive: self.escrow.receive,
+                expires_at: self.escrow.expires_at,
+            },
+            Clock::get()?.unix_timestamp,
+        )?;
+        self.escrow.listed = true;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct DelistOffer<'info> {
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Account<'info, OfferBook>,
+}
+
+impl<'info> DelistOffer<'info> {
+    pub fn delist(&mut self) -> Result<()> {
+        require!(self.escrow.listed, EscrowError::NotListed);
+
+        self.offer_book.remove(&self.escrow.key());
+        self.escrow.listed = false;
+
+        Ok(())
+    }
+}
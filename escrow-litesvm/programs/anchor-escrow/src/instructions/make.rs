@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
 use crate::state::Escrow;
+use crate::utils::transfer_checked_with_hook;
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -40,20 +41,41 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        taker: Option<Pubkey>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive,
+            deposit,
+            taker,
+            not_before: None,
+            expires_at: None,
+            listed: false,
             bump: bumps.escrow,
         });
 
         Ok(())
     }
 
-    pub fn deposit(&mut self, deposit: u64) -> Result<()> {
+    pub fn set_window(&mut self, not_before: Option<i64>, expires_at: Option<i64>) -> Result<()> {
+        self.escrow
+            .set_window(not_before, expires_at, Clock::get()?.unix_timestamp)
+    }
+
+    pub fn deposit(
+        &mut self,
+        deposit: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -63,9 +85,14 @@ impl<'info> Make<'info> {
             mint: self.mint_a.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_ctx, deposit, self.mint_a.decimals)?;
 
-        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)?;
+        // transfer fee mints withhold part of the deposit, record what the vault actually holds
+        self.vault.reload()?;
+        self.escrow.deposit = self.vault.amount;
 
         Ok(())
     }
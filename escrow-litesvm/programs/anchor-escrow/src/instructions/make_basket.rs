@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::EscrowError;
+use crate::state::{Basket, BasketItem, MAX_BASKET_ITEMS};
+use crate::utils::transfer_checked_with_hook;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Basket::INIT_SPACE,
+    )]
+    pub basket: Account<'info, Basket>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    pub fn init_basket(
+        &mut self,
+        seed: u64,
+        asked: Vec<BasketItem>,
+        taker: Option<Pubkey>,
+        bumps: &MakeBasketBumps,
+    ) -> Result<()> {
+        require!(!asked.is_empty(), EscrowError::EmptyBasket);
+        require!(asked.len() <= MAX_BASKET_ITEMS, EscrowError::BasketFull);
+        require!(
+            asked.iter().all(|item| item.amount > 0),
+            EscrowError::InvalidAmount
+        );
+
+        self.basket.set_inner(Basket {
+            seed,
+            maker: self.maker.key(),
+            taker,
+            offered: Vec::new(),
+            asked,
+            bump: bumps.basket,
+        });
+
+        Ok(())
+    }
+}
+
+// Every offered mint gets its own vault, the next free index is the number of offered items
+#[derive(Accounts)]
+pub struct DepositBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Account<'info, Basket>,
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"basket_vault", basket.key().as_ref(), &[basket.offered.len() as u8]],
+        bump,
+        token::mint = mint,
+        token::authority = basket,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DepositBasket<'info> {
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        require!(
+            self.basket.offered.len() < MAX_BASKET_ITEMS,
+            EscrowError::BasketFull
+        );
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.maker_ata.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+            mint: self.mint.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_ctx, amount, self.mint.decimals)?;
+
+        self.vault.reload()?;
+        self.basket.offered.push(BasketItem {
+            mint: self.mint.key(),
+            amount: self.vault.amount,
+        });
+
+        Ok(())
+    }
+}
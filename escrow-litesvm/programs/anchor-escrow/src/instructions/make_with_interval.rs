@@ -1,79 +0,0 @@
-use anchor_lang::prelude::*;
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
-};
-
-use crate::state::EscrowInterval;
-
-#[derive(Accounts)]
-#[instruction(seed: u64)]
-pub struct MakeInterval<'info> {
-    #[account(mut)]
-    pub maker: Signer<'info>,
-    pub mint_a: InterfaceAccount<'info, Mint>,
-    pub mint_b: InterfaceAccount<'info, Mint>,
-    #[account(
-        mut,
-        associated_token::mint = mint_a,
-        associated_token::authority = maker,
-    )]
-    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
-    #[account(
-        init,
-        payer = maker,
-        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
-        bump,
-        space = 8 + EscrowInterval::INIT_SPACE,
-    )]
-    pub escrow: Account<'info, EscrowInterval>,
-    #[account(
-        init,
-        payer = maker,
-        associated_token::mint = mint_a,
-        associated_token::authority = escrow,
-    )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
-}
-
-impl<'info> MakeInterval<'info> {
-    pub fn init_escrow_with_interval(
-        &mut self,
-        seed: u64,
-        receive: u64,
-        interval: u64,
-        bumps: &MakeIntervalBumps,
-    ) -> Result<()> {
-        self.escrow.set_inner(EscrowInterval {
-            seed,
-            interval: (Clock::get()?.unix_timestamp as u64) + interval,
-            maker: self.maker.key(),
-            mint_a: self.mint_a.key(),
-            mint_b: self.mint_b.key(),
-            receive,
-            bump: bumps.escrow,
-        });
-
-        Ok(())
-    }
-
-    pub fn deposit(&mut self, deposit: u64) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = TransferChecked {
-            from: self.maker_ata_a.to_account_info(),
-            to: self.vault.to_account_info(),
-            authority: self.maker.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-
-        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)?;
-
-        Ok(())
-    }
-}
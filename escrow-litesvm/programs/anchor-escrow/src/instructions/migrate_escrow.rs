@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::EscrowError;
+use crate::state::{decode_legacy_escrow, Escrow};
+
+// Rewrites an escrow made before the time window into the current layout, in place. Anyone
+// can run it, the payer only tops up the rent for the larger account
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: legacy layout, decoded and checked against its PDA in `migrate`
+    #[account(mut, owner = crate::ID)]
+    pub escrow: UncheckedAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateEscrow<'info> {
+    pub fn migrate(&mut self) -> Result<()> {
+        let escrow = decode_legacy_escrow(&self.escrow.try_borrow_data()?, self.vault.amount)?;
+
+        let address = Pubkey::create_program_address(
+            &[
+                b"escrow",
+                escrow.maker.as_ref(),
+                &escrow.seed.to_le_bytes(),
+                &[escrow.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| EscrowError::UnknownLayout)?;
+        require_keys_eq!(address, self.escrow.key(), EscrowError::UnknownLayout);
+        require_keys_eq!(escrow.mint_a, self.mint_a.key(), EscrowError::UnknownLayout);
+
+        let space = 8 + Escrow::INIT_SPACE;
+        let rent = Rent::get()?.minimum_balance(space);
+        let top_up = rent.saturating_sub(self.escrow.lamports());
+        if top_up > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.payer.to_account_info(),
+                    to: self.escrow.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, top_up)?;
+        }
+
+        self.escrow.realloc(space, true)?;
+        escrow.try_serialize(&mut &mut self.escrow.try_borrow_mut_data()?[..])
+    }
+}
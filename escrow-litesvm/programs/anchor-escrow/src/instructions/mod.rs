@@ -1,15 +1,23 @@
+pub mod list_offer;
 pub mod make;
+pub mod make_basket;
+pub mod migrate_escrow;
 pub mod refund;
+pub mod refund_basket;
+pub mod refund_expired;
 pub mod take;
+pub mod take_basket;
+pub mod take_partial;
+pub mod update_terms;
 
-pub mod make_with_interval;
-pub mod refund_with_interval;
-pub mod take_with_interval;
-
+pub use list_offer::*;
 pub use make::*;
+pub use make_basket::*;
+pub use migrate_escrow::*;
 pub use refund::*;
+pub use refund_basket::*;
+pub use refund_expired::*;
 pub use take::*;
-
-pub use make_with_interval::*;
-pub use refund_with_interval::*;
-pub use take_with_interval::*;
+pub use take_basket::*;
+pub use take_partial::*;
+pub use update_terms::*;
@@ -1,12 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account};
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
-use crate::state::Escrow;
+use crate::state::{Escrow, OfferBook};
+use crate::utils::{harvest_fees, sync_offer_book, transfer_checked_with_hook};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
     #[account(mut)]
     maker: Signer<'info>,
+    #[account(mut)]
     mint_a: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
@@ -29,19 +33,30 @@ pub struct Refund<'info> {
         associated_token::authority = escrow,
     )]
     vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    offer_book: Option<Account<'info, OfferBook>>,
     token_program: Interface<'info, TokenInterface>,
     system_program: Program<'info, System>,
 }
 
 impl<'info> Refund<'info> {
-    pub fn refund_and_close_vault(&mut self) -> Result<()> {
+    pub fn refund_and_close_vault(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), false)?;
+
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.key.as_ref(),
             &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump]
+            &[self.escrow.bump],
         ]];
-        
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -51,9 +66,16 @@ impl<'info> Refund<'info> {
             authority: self.escrow.to_account_info(),
         };
 
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_context, self.vault.amount, self.mint_a.decimals)?;
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+        harvest_fees(
+            &self.token_program,
+            &self.mint_a,
+            self.vault.to_account_info(),
+        )?;
 
         let cpi_program = self.token_program.to_account_info();
 
@@ -66,7 +88,7 @@ impl<'info> Refund<'info> {
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
         close_account(cpi_context)?;
-        
+
         Ok(())
     }
-}
\ No newline at end of file
+}
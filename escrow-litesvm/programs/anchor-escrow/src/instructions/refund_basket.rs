@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+use crate::state::Basket;
+use crate::utils::withdraw_basket_vault;
+
+// The offered items are passed as remaining accounts: [mint, vault, maker_account, token_program],
+// followed by the extra accounts of any transfer hook mints
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Account<'info, Basket>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    pub fn refund_and_close_vaults(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let basket = &self.basket;
+        require!(
+            remaining_accounts.len() >= 4 * basket.offered.len(),
+            EscrowError::InvalidBasketAccounts
+        );
+
+        let (item_accounts, hook_accounts) = remaining_accounts.split_at(4 * basket.offered.len());
+        for (index, (item, accounts)) in basket
+            .offered
+            .iter()
+            .zip(item_accounts.chunks(4))
+            .enumerate()
+        {
+            withdraw_basket_vault(
+                basket,
+                index,
+                item,
+                accounts,
+                hook_accounts,
+                self.maker.to_account_info(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -2,83 +2,62 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
-        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
     },
 };
 
-use crate::state::EscrowInterval;
+use crate::state::{Escrow, OfferBook};
+use crate::utils::{harvest_fees, sync_offer_book, transfer_checked_with_hook};
 
-//Create context
+// Anyone can return the deposit of an expired escrow to its maker
 #[derive(Accounts)]
-pub struct TakeInterval<'info> {
+pub struct RefundExpired<'info> {
     #[account(mut)]
-    pub taker: Signer<'info>,
+    pub caller: Signer<'info>,
     #[account(mut)]
     pub maker: SystemAccount<'info>,
+    #[account(mut)]
     pub mint_a: InterfaceAccount<'info, Mint>,
-    pub mint_b: InterfaceAccount<'info, Mint>,
     #[account(
         init_if_needed,
-        payer = taker,
+        payer = caller,
         associated_token::mint = mint_a,
-        associated_token::authority = taker,
-    )]
-    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
-    #[account(
-        mut,
-        associated_token::mint = mint_b,
-        associated_token::authority = taker,
-    )]
-    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
-    #[account(
-        init_if_needed,
-        payer = taker,
-        associated_token::mint = mint_b,
         associated_token::authority = maker,
     )]
-    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         close = maker,
-        has_one = maker,
         has_one = mint_a,
-        has_one = mint_b,
+        has_one = maker,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
-    pub escrow: Account<'info, EscrowInterval>,
+    pub escrow: Account<'info, Escrow>,
     #[account(
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = escrow,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Account<'info, OfferBook>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-//Deposit tokens from taker to maker
-//Transfer tokens from vault to taker
-//Close vault account
-impl<'info> TakeInterval<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = TransferChecked {
-            from: self.taker_ata_b.to_account_info(),
-            to: self.maker_ata_b.to_account_info(),
-            authority: self.taker.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+impl<'info> RefundExpired<'info> {
+    pub fn refund_and_close_vault(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), false)?;
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
-    }
-
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.key.as_ref(),
@@ -90,14 +69,21 @@ impl<'info> TakeInterval<'info> {
 
         let cpi_accounts = TransferChecked {
             from: self.vault.to_account_info(),
-            to: self.taker_ata_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
             mint: self.mint_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
         };
 
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_context, self.vault.amount, self.mint_a.decimals)?;
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+        harvest_fees(
+            &self.token_program,
+            &self.mint_a,
+            self.vault.to_account_info(),
+        )?;
 
         let cpi_program = self.token_program.to_account_info();
 
@@ -1,80 +0,0 @@
-use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{
-    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-    TransferChecked,
-};
-
-use crate::error::EscrowError;
-use crate::state::EscrowInterval;
-
-#[derive(Accounts)]
-pub struct RefundInterval<'info> {
-    #[account(mut)]
-    maker: Signer<'info>,
-    mint_a: InterfaceAccount<'info, Mint>,
-    #[account(
-        mut,
-        associated_token::mint = mint_a,
-        associated_token::authority = maker,
-    )]
-    maker_ata_a: InterfaceAccount<'info, TokenAccount>,
-    #[account(
-        mut,
-        close = maker,
-        has_one = mint_a,
-        has_one = maker,
-        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
-        bump = escrow.bump,
-    )]
-    pub escrow: Account<'info, EscrowInterval>,
-    #[account(
-        mut,
-        associated_token::mint = mint_a,
-        associated_token::authority = escrow,
-    )]
-    vault: InterfaceAccount<'info, TokenAccount>,
-    token_program: Interface<'info, TokenInterface>,
-    system_program: Program<'info, System>,
-}
-
-impl<'info> RefundInterval<'info> {
-    pub fn refund_and_close_vault(&mut self) -> Result<()> {
-        // require!(
-        //     self.escrow.interval >= (Clock::get()?.unix_timestamp as u64),
-        //     EscrowError::NeedToWait
-        // );
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            self.maker.key.as_ref(),
-            &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump],
-        ]];
-
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
-            to: self.maker_ata_a.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
-
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
-
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
-
-        close_account(cpi_context)?;
-
-        Ok(())
-    }
-}
@@ -1,7 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account}};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
 
-use crate::state::Escrow;
+use crate::error::EscrowError;
+use crate::state::{Escrow, OfferBook};
+use crate::utils::{amount_with_fee, harvest_fees, sync_offer_book, transfer_checked_with_hook};
 
 //Create context
 #[derive(Accounts)]
@@ -10,6 +17,7 @@ pub struct Take<'info> {
     pub taker: Signer<'info>,
     #[account(mut)]
     pub maker: SystemAccount<'info>,
+    #[account(mut)]
     pub mint_a: InterfaceAccount<'info, Mint>,
     pub mint_b: InterfaceAccount<'info, Mint>,
     #[account(
@@ -40,6 +48,7 @@ pub struct Take<'info> {
         has_one = mint_b,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
+        constraint = escrow.taker.is_none() || escrow.taker == Some(taker.key()) @ EscrowError::UnauthorizedTaker,
     )]
     pub escrow: Account<'info, Escrow>,
     #[account(
@@ -48,6 +57,12 @@ pub struct Take<'info> {
         associated_token::authority = escrow,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Account<'info, OfferBook>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -57,7 +72,9 @@ pub struct Take<'info> {
 //Transfer tokens from vault to taker
 //Close vault account
 impl<'info> Take<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
+    pub fn deposit(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let received_before = self.maker_ata_b.amount;
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -67,17 +84,33 @@ impl<'info> Take<'info> {
             mint: self.mint_b.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        let amount = amount_with_fee(&self.mint_b, self.escrow.receive)?;
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        transfer_checked_with_hook(cpi_ctx, amount, self.mint_b.decimals)?;
+
+        self.maker_ata_b.reload()?;
+        require!(
+            self.maker_ata_b.amount.saturating_sub(received_before) >= self.escrow.receive,
+            EscrowError::ReceivedTooLittle
+        );
+
+        Ok(())
     }
 
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    pub fn withdraw_and_close_vault(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), false)?;
+
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.key.as_ref(),
             &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump]
+            &[self.escrow.bump],
         ]];
 
         let cpi_program = self.token_program.to_account_info();
@@ -89,9 +122,16 @@ impl<'info> Take<'info> {
             mint: self.mint_a.to_account_info(),
         };
 
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_context, self.vault.amount, self.mint_a.decimals)?;
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+        harvest_fees(
+            &self.token_program,
+            &self.mint_a,
+            self.vault.to_account_info(),
+        )?;
 
         let cpi_program = self.token_program.to_account_info();
 
@@ -105,4 +145,4 @@ impl<'info> Take<'info> {
 
         close_account(cpi_context)
     }
-}
\ No newline at end of file
+}
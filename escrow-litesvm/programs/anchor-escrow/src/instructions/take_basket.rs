@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::EscrowError;
+use crate::state::Basket;
+use crate::utils::{amount_with_fee, transfer_checked_with_hook, withdraw_basket_vault};
+
+// The basket items are passed as remaining accounts, four per item:
+// asked items first as [mint, taker_account, maker_account, token_program],
+// then offered items as [mint, vault, taker_account, token_program].
+// Any accounts after those are the extra accounts of transfer hook mints
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump,
+        constraint = basket.taker.is_none() || basket.taker == Some(taker.key()) @ EscrowError::UnauthorizedTaker,
+    )]
+    pub basket: Account<'info, Basket>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBasket<'info> {
+    pub fn swap(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let basket = &self.basket;
+        require!(!basket.offered.is_empty(), EscrowError::EmptyBasket);
+        let item_accounts_len = 4 * (basket.asked.len() + basket.offered.len());
+        require!(
+            remaining_accounts.len() >= item_accounts_len,
+            EscrowError::InvalidBasketAccounts
+        );
+
+        let (item_accounts, hook_accounts) = remaining_accounts.split_at(item_accounts_len);
+        let (asked_accounts, offered_accounts) = item_accounts.split_at(4 * basket.asked.len());
+
+        for (item, accounts) in basket.asked.iter().zip(asked_accounts.chunks(4)) {
+            let [mint, from, to, token_program] = accounts else {
+                return err!(EscrowError::InvalidBasketAccounts);
+            };
+            require_keys_eq!(mint.key(), item.mint, EscrowError::InvalidBasketAccounts);
+
+            let mint = InterfaceAccount::<Mint>::try_from(mint)?;
+            let mut to = InterfaceAccount::<TokenAccount>::try_from(to)?;
+            require_keys_eq!(
+                to.owner,
+                self.maker.key(),
+                EscrowError::InvalidBasketAccounts
+            );
+
+            let received_before = to.amount;
+
+            let cpi_accounts = TransferChecked {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: self.taker.to_account_info(),
+                mint: mint.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(
+                Interface::<TokenInterface>::try_from(token_program)?.to_account_info(),
+                cpi_accounts,
+            )
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+            let amount = amount_with_fee(&mint, item.amount)?;
+
+            transfer_checked_with_hook(cpi_ctx, amount, mint.decimals)?;
+
+            to.reload()?;
+            require!(
+                to.amount.saturating_sub(received_before) >= item.amount,
+                EscrowError::ReceivedTooLittle
+            );
+        }
+
+        for (index, (item, accounts)) in basket
+            .offered
+            .iter()
+            .zip(offered_accounts.chunks(4))
+            .enumerate()
+        {
+            withdraw_basket_vault(
+                basket,
+                index,
+                item,
+                accounts,
+                hook_accounts,
+                self.maker.to_account_info(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
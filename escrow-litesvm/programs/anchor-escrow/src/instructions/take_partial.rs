@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::error::EscrowError;
+use crate::state::{Escrow, OfferBook};
+use crate::utils::{amount_with_fee, harvest_fees, sync_offer_book, transfer_checked_with_hook};
+
+#[derive(Accounts)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.taker.is_none() || escrow.taker == Some(taker.key()) @ EscrowError::UnauthorizedTaker,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Account<'info, OfferBook>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Deposit part of the asked tokens from taker to maker
+//Transfer the proportional amount of tokens from vault to taker
+//Close vault and escrow once the offer is fully filled
+impl<'info> TakePartial<'info> {
+    pub fn deposit(
+        &mut self,
+        amount_b: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount_b > 0, EscrowError::InvalidAmount);
+        require!(amount_b <= self.escrow.receive, EscrowError::AmountTooBig);
+
+        let received_before = self.maker_ata_b.amount;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        let amount = amount_with_fee(&self.mint_b, amount_b)?;
+
+        transfer_checked_with_hook(cpi_ctx, amount, self.mint_b.decimals)?;
+
+        self.maker_ata_b.reload()?;
+        require!(
+            self.maker_ata_b.amount.saturating_sub(received_before) >= amount_b,
+            EscrowError::ReceivedTooLittle
+        );
+
+        Ok(())
+    }
+
+    pub fn withdraw_and_update_escrow(
+        &mut self,
+        amount_b: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let filled = amount_b == self.escrow.receive;
+
+        // the last fill sweeps whatever is left in the vault, including rounding dust
+        let amount_a = if filled {
+            self.vault.amount
+        } else {
+            (self.escrow.deposit as u128)
+                .checked_mul(amount_b as u128)
+                .and_then(|amount| amount.checked_div(self.escrow.receive as u128))
+                .and_then(|amount| u64::try_from(amount).ok())
+                .ok_or(EscrowError::InvalidAmount)?
+        };
+        require!(amount_a > 0, EscrowError::InvalidAmount);
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(cpi_context, amount_a, self.mint_a.decimals)?;
+
+        if !filled {
+            self.escrow.receive -= amount_b;
+            self.escrow.deposit -= amount_a;
+
+            return sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), true);
+        }
+
+        sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), false)?;
+
+        harvest_fees(
+            &self.token_program,
+            &self.mint_a,
+            self.vault.to_account_info(),
+        )?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        close_account(cpi_context)?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
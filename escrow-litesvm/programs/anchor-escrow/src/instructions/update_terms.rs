@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+use crate::state::{Escrow, OfferBook};
+use crate::utils::sync_offer_book;
+
+// Lets the maker reprice an open escrow without refunding and making it again
+#[derive(Accounts)]
+pub struct UpdateTerms<'info> {
+    pub maker: Signer<'info>,
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"offer_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = offer_book.bump,
+    )]
+    pub offer_book: Option<Account<'info, OfferBook>>,
+}
+
+impl<'info> UpdateTerms<'info> {
+    pub fn update_terms(&mut self, receive: u64, expires_at: Option<i64>) -> Result<()> {
+        require!(receive > 0, EscrowError::InvalidAmount);
+
+        let not_before = self.escrow.not_before;
+        self.escrow
+            .set_window(not_before, expires_at, Clock::get()?.unix_timestamp)?;
+        self.escrow.receive = receive;
+
+        sync_offer_book(&mut self.escrow, self.offer_book.as_mut(), true)
+    }
+}
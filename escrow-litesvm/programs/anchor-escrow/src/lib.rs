@@ -7,9 +7,11 @@ pub mod error;
 mod instructions;
 mod state;
 mod tests;
+mod utils;
 
 use error::EscrowError;
 use instructions::*;
+use state::BasketItem;
 
 declare_id!("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
 
@@ -17,46 +19,173 @@ declare_id!("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
 pub mod anchor_escrow {
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
-        ctx.accounts.deposit(deposit)
+    pub fn make<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, deposit, receive, None, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)
+    }
+
+    // Same as `make`, but only `taker` can take the escrow
+    pub fn make_private<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        taker: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, deposit, receive, Some(taker), &ctx.bumps)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)
+    }
+
+    pub fn make_with_window<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        taker: Option<Pubkey>,
+        not_before: Option<i64>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow(seed, deposit, receive, taker, &ctx.bumps)?;
+        ctx.accounts.set_window(not_before, expires_at)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)
+    }
+
+    pub fn refund<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+        ctx.accounts.refund_and_close_vault(ctx.remaining_accounts)
+    }
+
+    pub fn refund_expired<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundExpired<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::NotExpired
+        );
+        ctx.accounts.refund_and_close_vault(ctx.remaining_accounts)
     }
 
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
-        ctx.accounts.refund_and_close_vault()
+    pub fn take<'info>(ctx: Context<'_, '_, 'info, 'info, Take<'info>>) -> Result<()> {
+        ctx.accounts
+            .escrow
+            .check_window(Clock::get()?.unix_timestamp)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)?;
+        ctx.accounts
+            .withdraw_and_close_vault(ctx.remaining_accounts)
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.deposit()?;
-        ctx.accounts.withdraw_and_close_vault()
+    // Same as `take`, but fails if the escrow no longer asks for `expected_receive`
+    pub fn take_checked<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        expected_receive: u64,
+    ) -> Result<()> {
+        ctx.accounts.escrow.check_receive(expected_receive)?;
+        take(ctx)
     }
 
-    pub fn make_with_interval(
-        ctx: Context<MakeInterval>,
+    pub fn take_partial<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakePartial<'info>>,
+        amount_b: u64,
+        expected_receive: u64,
+    ) -> Result<()> {
+        ctx.accounts.escrow.check_receive(expected_receive)?;
+        ctx.accounts
+            .escrow
+            .check_window(Clock::get()?.unix_timestamp)?;
+        ctx.accounts.deposit(amount_b, ctx.remaining_accounts)?;
+        ctx.accounts
+            .withdraw_and_update_escrow(amount_b, ctx.remaining_accounts)
+    }
+
+    pub fn update_terms(
+        ctx: Context<UpdateTerms>,
+        receive: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_terms(receive, expires_at)
+    }
+
+    pub fn list_offer(ctx: Context<ListOffer>) -> Result<()> {
+        ctx.accounts.list(&ctx.bumps)
+    }
+
+    pub fn delist_offer(ctx: Context<DelistOffer>) -> Result<()> {
+        ctx.accounts.delist()
+    }
+
+    pub fn make_basket(
+        ctx: Context<MakeBasket>,
+        seed: u64,
+        asked: Vec<BasketItem>,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.init_basket(seed, asked, taker, &ctx.bumps)
+    }
+
+    pub fn deposit_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositBasket<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, ctx.remaining_accounts)
+    }
+
+    pub fn take_basket<'info>(ctx: Context<'_, '_, 'info, 'info, TakeBasket<'info>>) -> Result<()> {
+        ctx.accounts.swap(ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBasket<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.refund_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    // Interval entry points are kept for existing clients, they map onto the escrow time window
+
+    pub fn make_with_interval<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
         seed: u64,
         deposit: u64,
         interval: u64,
         receive: u64,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let not_before = i64::try_from(interval)
+            .ok()
+            .and_then(|interval| now.checked_add(interval))
+            .ok_or(EscrowError::InvalidWindow)?;
         ctx.accounts
-            .init_escrow_with_interval(seed, receive, interval, &ctx.bumps)?;
-        ctx.accounts.deposit(deposit)
+            .init_escrow(seed, deposit, receive, None, &ctx.bumps)?;
+        ctx.accounts.set_window(Some(not_before), None)?;
+        ctx.accounts.deposit(deposit, ctx.remaining_accounts)
     }
 
-    pub fn refund_with_interval(ctx: Context<RefundInterval>) -> Result<()> {
-        require!(
-            ctx.accounts.escrow.interval <= (Clock::get()?.unix_timestamp as u64),
-            EscrowError::NeedToWait
-        );
-        ctx.accounts.refund_and_close_vault()
+    pub fn refund_with_interval<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Refund<'info>>,
+    ) -> Result<()> {
+        if let Some(not_before) = ctx.accounts.escrow.not_before {
+            require!(
+                not_before <= Clock::get()?.unix_timestamp,
+                EscrowError::NeedToWait
+            );
+        }
+        ctx.accounts.refund_and_close_vault(ctx.remaining_accounts)
     }
 
-    pub fn take_with_interval(ctx: Context<TakeInterval>) -> Result<()> {
-        require!(
-            ctx.accounts.escrow.interval <= (Clock::get()?.unix_timestamp as u64),
-            EscrowError::NeedToWait
-        );
-        ctx.accounts.deposit()?;
-        ctx.accounts.withdraw_and_close_vault()
+    pub fn take_with_interval<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+    ) -> Result<()> {
+        take(ctx)
+    }
+
+    // Escrows made before the time window are rewritten in place so the handlers above can read them
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        ctx.accounts.migrate()
     }
 }
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_BASKET_ITEMS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq)]
+pub struct BasketItem {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// Offered items sit in vaults seeded by their index: [b"basket_vault", basket, index]
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct Basket {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub taker: Option<Pubkey>,
+    #[max_len(4)]
+    pub offered: Vec<BasketItem>,
+    #[max_len(4)]
+    pub asked: Vec<BasketItem>,
+    pub bump: u8,
+}
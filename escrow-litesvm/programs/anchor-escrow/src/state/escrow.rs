@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::error::EscrowError;
+
 #[account]
 #[derive(InitSpace, Debug)]
 pub struct Escrow {
@@ -8,5 +10,51 @@ pub struct Escrow {
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub receive: u64,
+    pub deposit: u64,
+    pub taker: Option<Pubkey>,
+    pub not_before: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub listed: bool,
     pub bump: u8,
-}
\ No newline at end of file
+}
+
+impl Escrow {
+    pub fn set_window(
+        &mut self,
+        not_before: Option<i64>,
+        expires_at: Option<i64>,
+        now: i64,
+    ) -> Result<()> {
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > now, EscrowError::InvalidWindow);
+            if let Some(not_before) = not_before {
+                require!(not_before < expires_at, EscrowError::InvalidWindow);
+            }
+        }
+
+        self.not_before = not_before;
+        self.expires_at = expires_at;
+
+        Ok(())
+    }
+
+    pub fn check_window(&self, now: i64) -> Result<()> {
+        if let Some(not_before) = self.not_before {
+            require!(now >= not_before, EscrowError::NeedToWait);
+        }
+        require!(!self.is_expired(now), EscrowError::Expired);
+
+        Ok(())
+    }
+
+    // Takers pass the `receive` they priced the escrow at, so a repricing landing first fails them
+    pub fn check_receive(&self, expected_receive: u64) -> Result<()> {
+        require!(self.receive == expected_receive, EscrowError::TermsChanged);
+
+        Ok(())
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::error::EscrowError;
+use crate::state::Escrow;
+
+// Escrow layouts written before the time window, read once by `migrate_escrow`
+
+#[derive(AnchorDeserialize)]
+struct EscrowV1 {
+    seed: u64,
+    maker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    receive: u64,
+    bump: u8,
+}
+
+#[derive(AnchorDeserialize)]
+struct EscrowIntervalV1 {
+    seed: u64,
+    interval: u64,
+    maker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    receive: u64,
+    bump: u8,
+}
+
+pub fn escrow_interval_discriminator() -> [u8; 8] {
+    hash(b"account:EscrowInterval").to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+// Decodes a legacy escrow into the current layout. The legacy accounts did not record the
+// deposit, so it is taken from the vault
+pub fn decode_legacy_escrow(data: &[u8], deposit: u64) -> Result<Escrow> {
+    require!(data.len() >= 8, EscrowError::UnknownLayout);
+    let (discriminator, mut body) = data.split_at(8);
+
+    if discriminator == Escrow::DISCRIMINATOR && data.len() < 8 + Escrow::INIT_SPACE {
+        let old = EscrowV1::deserialize(&mut body)?;
+        return Ok(Escrow {
+            seed: old.seed,
+            maker: old.maker,
+            mint_a: old.mint_a,
+            mint_b: old.mint_b,
+            receive: old.receive,
+            deposit,
+            taker: None,
+            not_before: None,
+            expires_at: None,
+            listed: false,
+            bump: old.bump,
+        });
+    }
+
+    if discriminator == escrow_interval_discriminator() {
+        let old = EscrowIntervalV1::deserialize(&mut body)?;
+        let not_before = i64::try_from(old.interval).map_err(|_| EscrowError::InvalidWindow)?;
+        return Ok(Escrow {
+            seed: old.seed,
+            maker: old.maker,
+            mint_a: old.mint_a,
+            mint_b: old.mint_b,
+            receive: old.receive,
+            deposit,
+            taker: None,
+            not_before: Some(not_before),
+            expires_at: None,
+            listed: false,
+            bump: old.bump,
+        });
+    }
+
+    err!(EscrowError::UnknownLayout)
+}
@@ -1,7 +1,9 @@
+pub mod basket;
 pub mod escrow;
+pub mod legacy;
+pub mod offer_book;
 
+pub use basket::*;
 pub use escrow::*;
-
-pub mod escrow_with_interval;
-
-pub use escrow_with_interval::*;
+pub use legacy::*;
+pub use offer_book::*;
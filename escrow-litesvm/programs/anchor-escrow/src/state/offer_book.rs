@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq)]
+pub struct Offer {
+    pub escrow: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: Option<i64>,
+}
+
+impl Offer {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    // Mint B asked per mint A deposited, compared without dividing
+    fn is_cheaper_than(&self, other: &Offer) -> bool {
+        (self.receive as u128) * (other.deposit as u128)
+            < (other.receive as u128) * (self.deposit as u128)
+    }
+}
+
+// Open escrows of a mint pair, the cheapest offer for the taker comes first
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct OfferBook {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    #[max_len(32)]
+    pub offers: Vec<Offer>,
+    pub bump: u8,
+}
+
+impl OfferBook {
+    pub const MAX_OFFERS: usize = 32;
+    // Smallest mint A deposit worth a slot in the book
+    pub const MIN_OFFER_DEPOSIT: u64 = 1_000;
+
+    // Expired offers make room first, a full book then only takes an offer cheaper than its
+    // most expensive one, which is dropped
+    pub fn insert(&mut self, offer: Offer, now: i64) -> Result<()> {
+        require!(
+            offer.deposit >= Self::MIN_OFFER_DEPOSIT,
+            EscrowError::OfferTooSmall
+        );
+        require!(!offer.is_expired(now), EscrowError::Expired);
+
+        self.offers.retain(|listed| !listed.is_expired(now));
+        if self.offers.len() >= Self::MAX_OFFERS {
+            match self.offers.last() {
+                Some(worst) if offer.is_cheaper_than(worst) => {
+                    self.offers.pop();
+                }
+                _ => return err!(EscrowError::OfferBookFull),
+            }
+        }
+
+        // equally priced offers keep their listing order
+        let index = self
+            .offers
+            .partition_point(|listed| !offer.is_cheaper_than(listed));
+        self.offers.insert(index, offer);
+
+        Ok(())
+    }
+
+    // Returns whether the escrow was still in the book, it may have been dropped by `insert`
+    pub fn remove(&mut self, escrow: &Pubkey) -> bool {
+        let len = self.offers.len();
+        self.offers.retain(|offer| offer.escrow != *escrow);
+        self.offers.len() < len
+    }
+
+    pub fn best(&self, now: i64) -> Option<&Offer> {
+        self.offers.iter().find(|offer| !offer.is_expired(now))
+    }
+}
@@ -4,6 +4,7 @@ mod tests {
     use {
         anchor_lang::{
             prelude::{msg, Clock, SolanaSysvar},
+            solana_program::hash::hash,
             solana_program::{program_pack::Pack, sysvar::SysvarId},
             AccountDeserialize, InstructionData, Key, ToAccountMetas,
         },
@@ -17,7 +18,7 @@ mod tests {
         },
         solana_account::Account,
         solana_address::Address,
-        solana_instruction::Instruction,
+        solana_instruction::{AccountMeta, Instruction},
         solana_keypair::Keypair,
         solana_message::Message,
         solana_native_token::LAMPORTS_PER_SOL,
@@ -25,13 +26,28 @@ mod tests {
         solana_rpc_client::rpc_client::RpcClient,
         solana_sdk_ids::system_program::ID as SYSTEM_PROGRAM_ID,
         solana_signer::Signer,
+        solana_system_interface::instruction::create_account,
         solana_transaction::Transaction,
+        spl_tlv_account_resolution::{
+            account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+        },
+        spl_token_2022::{
+            extension::{
+                transfer_fee::instruction::initialize_transfer_fee_config, transfer_hook,
+                ExtensionType, StateWithExtensions,
+            },
+            instruction::initialize_mint2,
+        },
+        spl_transfer_hook_interface::instruction::ExecuteInstruction,
         std::{path::PathBuf, str::FromStr},
         // borsh::BorshDeserialize;
     };
 
     static PROGRAM_ID: Pubkey = crate::ID;
 
+    static HOOK_PROGRAM_ID: Pubkey =
+        Pubkey::from_str_const("DhzyDgCmmQzVC4vEcj2zRGUyN8Mt5JynfdGLKkBcRGaX");
+
     fn setup() -> (
         LiteSVM,
         Keypair,
@@ -168,6 +184,270 @@ mod tests {
         )
     }
 
+    struct Token2022Setup {
+        program: LiteSVM,
+        payer: Keypair,
+        payer2: Keypair,
+        maker: Pubkey,
+        taker: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        escrow: Pubkey,
+        vault: Pubkey,
+        maker_ata_a: Pubkey,
+        maker_ata_b: Pubkey,
+        taker_ata_a: Pubkey,
+        taker_ata_b: Pubkey,
+    }
+
+    // Same accounts as `setup`, but both mints are owned by Token-2022. With `transfer_fee` both
+    // mints withhold 1% of every transfer, with `transfer_hook` transfers of mint A go through
+    // the whitelist transfer hook program
+    fn setup_token_2022(transfer_fee: bool, transfer_hook: bool) -> Token2022Setup {
+        let mut program = LiteSVM::new();
+        let payer = Keypair::new();
+        let maker = payer.pubkey();
+        let payer2 = Keypair::new();
+        let taker = payer2.pubkey();
+
+        program
+            .airdrop(&maker, 1000 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to payer");
+        program
+            .airdrop(&taker, 1000 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to payer");
+
+        let so_path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy/anchor_escrow.so");
+        let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
+        program.add_program(PROGRAM_ID, &program_data);
+
+        if transfer_hook {
+            let so_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../../../whitelist-transfer-hook/target/deploy/whitelist_transfer_hook.so");
+            let program_data =
+                std::fs::read(so_path).expect("Failed to read transfer hook SO file");
+            program.add_program(HOOK_PROGRAM_ID, &program_data);
+        }
+
+        let mint_a = create_token_2022_mint(&mut program, &payer, transfer_fee, transfer_hook);
+        let mint_b = create_token_2022_mint(&mut program, &payer, transfer_fee, false);
+
+        if transfer_hook {
+            init_extra_account_metas(&mut program, &mint_a);
+        }
+
+        let token_program = spl_token_2022::ID;
+
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_a)
+            .owner(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let maker_ata_b = CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_b)
+            .owner(&maker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let taker_ata_a = CreateAssociatedTokenAccount::new(&mut program, &payer2, &mint_a)
+            .owner(&taker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let taker_ata_b = CreateAssociatedTokenAccount::new(&mut program, &payer2, &mint_b)
+            .owner(&taker)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+
+        let escrow = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &123u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        let vault = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &escrow,
+            &mint_a,
+            &token_program,
+        );
+
+        Token2022Setup {
+            program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        }
+    }
+
+    fn create_token_2022_mint(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        transfer_fee: bool,
+        transfer_hook: bool,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let token_program = spl_token_2022::ID;
+
+        let mut extensions = vec![];
+        if transfer_fee {
+            extensions.push(ExtensionType::TransferFeeConfig);
+        }
+        if transfer_hook {
+            extensions.push(ExtensionType::TransferHook);
+        }
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)
+                .unwrap();
+
+        let mut instructions = vec![create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            program.minimum_balance_for_rent_exemption(space),
+            space as u64,
+            &token_program,
+        )];
+        if transfer_fee {
+            instructions.push(
+                initialize_transfer_fee_config(
+                    &token_program,
+                    &mint.pubkey(),
+                    None,
+                    None,
+                    100,
+                    u64::MAX,
+                )
+                .unwrap(),
+            );
+        }
+        if transfer_hook {
+            instructions.push(
+                transfer_hook::instruction::initialize(
+                    &token_program,
+                    &mint.pubkey(),
+                    None,
+                    Some(HOOK_PROGRAM_ID),
+                )
+                .unwrap(),
+            );
+        }
+        instructions.push(
+            initialize_mint2(&token_program, &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+        );
+
+        let message = Message::new(&instructions, Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[payer, &mint], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        mint.pubkey()
+    }
+
+    // The hook resolves the whitelist entry of the source owner: [b"whitelist", owner]
+    fn init_extra_account_metas(program: &mut LiteSVM, mint: &Pubkey) {
+        let extra_account_metas = [ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"whitelist".to_vec(),
+                },
+                Seed::AccountKey { index: 3 },
+            ],
+            false,
+            false,
+        )
+        .unwrap()];
+
+        let mut data = vec![0; ExtraAccountMetaList::size_of(extra_account_metas.len()).unwrap()];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_account_metas).unwrap();
+
+        let (extra_account_meta_list, _) = Pubkey::find_program_address(
+            &[b"extra-account-metas", mint.as_ref()],
+            &HOOK_PROGRAM_ID,
+        );
+
+        program
+            .set_account(
+                extra_account_meta_list,
+                Account {
+                    lamports: program.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: HOOK_PROGRAM_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    fn whitelist(program: &mut LiteSVM, owner: &Pubkey) -> Pubkey {
+        let (whitelist, bump) =
+            Pubkey::find_program_address(&[b"whitelist", owner.as_ref()], &HOOK_PROGRAM_ID);
+
+        let mut data = hash(b"account:Whitelist").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&[1, bump]);
+
+        program
+            .set_account(
+                whitelist,
+                Account {
+                    lamports: program.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: HOOK_PROGRAM_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+
+        whitelist
+    }
+
+    // Accounts the hooked transfers of `mint` need, passed as remaining accounts
+    fn hook_accounts(mint: &Pubkey, owners: &[Pubkey]) -> Vec<AccountMeta> {
+        let (extra_account_meta_list, _) = Pubkey::find_program_address(
+            &[b"extra-account-metas", mint.as_ref()],
+            &HOOK_PROGRAM_ID,
+        );
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(HOOK_PROGRAM_ID, false),
+            AccountMeta::new_readonly(extra_account_meta_list, false),
+        ];
+        for owner in owners {
+            let (whitelist, _) =
+                Pubkey::find_program_address(&[b"whitelist", owner.as_ref()], &HOOK_PROGRAM_ID);
+            accounts.push(AccountMeta::new_readonly(whitelist, false));
+        }
+
+        accounts
+    }
+
+    fn token_2022_amount(program: &LiteSVM, account: &Pubkey) -> u64 {
+        let account = program.get_account(account).unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
     #[test]
     fn test_make() {
         // Setup the test environment by initializing LiteSVM and creating a payer keypair
@@ -363,6 +643,7 @@ mod tests {
                 maker_ata_b: maker_ata_b,
                 escrow: escrow,
                 vault: vault,
+                offer_book: None,
                 associated_token_program: asspciated_token_program,
                 token_program: token_program,
                 system_program: system_program,
@@ -397,7 +678,7 @@ mod tests {
     }
 
     #[test]
-    fn test_refund() {
+    fn test_take_partial() {
         // Setup the test environment by initializing LiteSVM and creating a payer keypair
         let (
             mut program,
@@ -415,21 +696,6 @@ mod tests {
             taker_ata_b,
         ) = setup();
 
-        // // Get the maker's public key from the payer keypair
-        // let maker = payer.pubkey();
-
-        msg!("Mint A: {}\n", mint_a);
-
-        msg!("Mint B: {}\n", mint_b);
-
-        msg!("Maker ATA A: {}\n", maker_ata_a);
-
-        msg!("Escrow PDA: {}\n", escrow);
-
-        msg!("Vault PDA: {}\n", vault);
-
-        // msg!("payer balance {}\n", maker.)
-
         // Define program IDs for associated token program, token program, and system program
         let asspciated_token_program = spl_associated_token_account::ID;
         let token_program = TOKEN_PROGRAM_ID;
@@ -440,99 +706,123 @@ mod tests {
             .send()
             .unwrap();
 
-        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
-        let maker_ata_a_data: spl_token::state::Account =
-            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
-
-        let maker_ata_a_balance_before_make: u64 = maker_ata_a_data.amount;
-
-        msg!(
-            "maker ata a balance before make {}",
-            maker_ata_a_balance_before_make
-        );
-        // Create the "Make" instruction to deposit tokens into the escrow
+        // Offer 100 of Mint A for 50 of Mint B
         let make_ix = Instruction {
             program_id: PROGRAM_ID,
             accounts: crate::accounts::Make {
-                maker: maker,
-                mint_a: mint_a,
-                mint_b: mint_b,
-                maker_ata_a: maker_ata_a,
-                escrow: escrow,
-                vault: vault,
+                maker,
+                mint_a,
+                mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
                 associated_token_program: asspciated_token_program,
-                token_program: token_program,
-                system_program: system_program,
+                token_program,
+                system_program,
             }
             .to_account_metas(None),
             data: crate::instruction::Make {
-                deposit: 10,
+                deposit: 100,
                 seed: 123u64,
-                receive: 10,
+                receive: 50,
             }
             .data(),
         };
 
-        // Create and send the transaction containing the "Make" instruction
         let message = Message::new(&[make_ix], Some(&payer.pubkey()));
         let recent_blockhash = program.latest_blockhash();
-
         let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
 
-        // Send the transaction and capture the result
-        let tx1 = program.send_transaction(transaction).unwrap();
-
-        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
-        let maker_ata_a_data: spl_token::state::Account =
-            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
-        let maker_ata_a_balance_after_make: u64 = maker_ata_a_data.amount;
-
-        msg!(
-            "maker ata a balance after make {}",
-            maker_ata_a_balance_after_make
-        );
-
-        assert_eq!(maker_ata_a_balance_after_make, 1000000000 - 10);
+        // Mint 1,000 tokens (with 6 decimal places) of Mint B to the takers's associated token account
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
 
-        // Create the "Make" instruction to deposit tokens into the escrow
-        let refund_ix = Instruction {
+        let take_partial_ix = |amount_b: u64, expected_receive: u64| Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::Refund {
-                maker: maker,
-                mint_a: mint_a,
-                maker_ata_a: maker_ata_a,
-                escrow: escrow,
-                vault: vault,
-                token_program: token_program,
-                system_program: system_program,
+            accounts: crate::accounts::TakePartial {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
             }
             .to_account_metas(None),
-            data: crate::instruction::Refund {}.data(),
+            data: crate::instruction::TakePartial {
+                amount_b,
+                expected_receive,
+            }
+            .data(),
         };
 
-        // Create and send the transaction containing the "Make" instruction
-        let message = Message::new(&[refund_ix], Some(&payer.pubkey()));
+        // Fill 20 of the 50 asked tokens
+        let message = Message::new(&[take_partial_ix(20, 50)], Some(&payer2.pubkey()));
         let recent_blockhash = program.latest_blockhash();
-
-        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
-        // Send the transaction and capture the result
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
         let tx = program.send_transaction(transaction).unwrap();
 
-        // Log transaction details
-        msg!("\nRefund transaction sucessfull");
+        msg!("\nPartial take transaction sucessfull");
         msg!("CUs Consumed: {}", tx.compute_units_consumed);
-        msg!("Tx Signature: {}", tx.signature);
 
-        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
-        let maker_ata_a_data: spl_token::state::Account =
-            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
-        let maker_ata_a_balance_after_refund: u64 = maker_ata_a_data.amount;
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data =
+            spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 40);
 
-        assert_eq!(maker_ata_a_balance_after_refund, 1000000000);
+        let escrow_account = program.get_account(&escrow).unwrap();
+        let escrow_data =
+            crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.receive, 30);
+        assert_eq!(escrow_data.deposit, 60);
+
+        // A taker still pricing the escrow at the old terms fails
+        let message = Message::new(&[take_partial_ix(10, 50)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        // Asking for more than what is left fails
+        program.expire_blockhash();
+        let message = Message::new(&[take_partial_ix(31, 30)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        // Fill the remaining 30, which closes the escrow
+        let message = Message::new(&[take_partial_ix(30, 30)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let maker_ata_b_account = program.get_account(&maker_ata_b).unwrap();
+        let maker_ata_b_data =
+            spl_token::state::Account::unpack(&maker_ata_b_account.data).unwrap();
+        assert_eq!(maker_ata_b_data.amount, 50);
+
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data =
+            spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 100);
+
+        assert!(program
+            .get_account(&vault)
+            .is_none_or(|account| account.lamports == 0));
+        assert!(program
+            .get_account(&escrow)
+            .is_none_or(|account| account.lamports == 0));
     }
 
     #[test]
-    fn test_make_with_interval() {
+    fn test_take_designated_taker() {
         // Setup the test environment by initializing LiteSVM and creating a payer keypair
         let (
             mut program,
@@ -550,63 +840,327 @@ mod tests {
             taker_ata_b,
         ) = setup();
 
-        // // Get the maker's public key from the payer keypair
-        // let maker = payer.pubkey();
-
-        msg!("Mint A: {}\n", mint_a);
-
-        msg!("Mint B: {}\n", mint_b);
-
-        msg!("Maker ATA A: {}\n", maker_ata_a);
-
-        msg!("Escrow PDA: {}\n", escrow);
-
-        msg!("Vault PDA: {}\n", vault);
-
-        // msg!("payer balance {}\n", maker.)
-
         // Define program IDs for associated token program, token program, and system program
         let asspciated_token_program = spl_associated_token_account::ID;
         let token_program = TOKEN_PROGRAM_ID;
         let system_program = SYSTEM_PROGRAM_ID;
 
+        // A second escrow of the same maker, reserved for the taker
+        let private_escrow = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &124u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let private_vault =
+            associated_token::get_associated_token_address(&private_escrow, &mint_a);
+
         // Mint 1,000 tokens (with 6 decimal places) of Mint A to the maker's associated token account
         MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
             .send()
             .unwrap();
 
-        // Create the "Make" instruction to deposit tokens into the escrow
-        let make_ix = Instruction {
+        // Mint 1,000 tokens (with 6 decimal places) of Mint B to the takers's associated token account
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
+
+        let make_ix = |escrow: Pubkey, vault: Pubkey, seed: u64, taker: Pubkey| Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::MakeInterval {
-                maker: maker,
-                mint_a: mint_a,
-                mint_b: mint_b,
-                maker_ata_a: maker_ata_a,
-                escrow: escrow,
-                vault: vault,
+            accounts: crate::accounts::Make {
+                maker,
+                mint_a,
+                mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
                 associated_token_program: asspciated_token_program,
-                token_program: token_program,
-                system_program: system_program,
+                token_program,
+                system_program,
             }
             .to_account_metas(None),
-            data: crate::instruction::MakeWithInterval {
-                seed: 123u64,
+            data: crate::instruction::MakePrivate {
                 deposit: 10,
-                interval: 120,
+                seed,
                 receive: 10,
+                taker,
             }
             .data(),
         };
 
-        // let clock_account = program.get_account(&Clock::id()).unwrap();
-        // let clock = Clock::try_from_slice(&clock_account.data).unwrap();
-        // let current_timestamp = clock.unix_timestamp;
-
-        // let clock = Clock::get();
-        // let current_time = clock.unwrap().unix_timestamp as u64;
-
-        // Create and send the transaction containing the "Make" instruction
+        let take_ix = |escrow: Pubkey, vault: Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Take {}.data(),
+        };
+
+        // The first escrow is reserved for someone else
+        let message = Message::new(
+            &[
+                make_ix(escrow, vault, 123u64, Pubkey::new_unique()),
+                make_ix(private_escrow, private_vault, 124u64, taker),
+            ],
+            Some(&payer.pubkey()),
+        );
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let escrow_account = program.get_account(&private_escrow).unwrap();
+        let escrow_data =
+            crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.taker, Some(taker));
+
+        // Taking an escrow reserved for another taker fails
+        let message = Message::new(&[take_ix(escrow, vault)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        // The designated taker can take its escrow
+        let message = Message::new(
+            &[take_ix(private_escrow, private_vault)],
+            Some(&payer2.pubkey()),
+        );
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nTake transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data =
+            spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 10);
+    }
+
+    #[test]
+    fn test_refund() {
+        // Setup the test environment by initializing LiteSVM and creating a payer keypair
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        // // Get the maker's public key from the payer keypair
+        // let maker = payer.pubkey();
+
+        msg!("Mint A: {}\n", mint_a);
+
+        msg!("Mint B: {}\n", mint_b);
+
+        msg!("Maker ATA A: {}\n", maker_ata_a);
+
+        msg!("Escrow PDA: {}\n", escrow);
+
+        msg!("Vault PDA: {}\n", vault);
+
+        // msg!("payer balance {}\n", maker.)
+
+        // Define program IDs for associated token program, token program, and system program
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        // Mint 1,000 tokens (with 6 decimal places) of Mint A to the maker's associated token account
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
+        let maker_ata_a_data: spl_token::state::Account =
+            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
+
+        let maker_ata_a_balance_before_make: u64 = maker_ata_a_data.amount;
+
+        msg!(
+            "maker ata a balance before make {}",
+            maker_ata_a_balance_before_make
+        );
+        // Create the "Make" instruction to deposit tokens into the escrow
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker: maker,
+                mint_a: mint_a,
+                mint_b: mint_b,
+                maker_ata_a: maker_ata_a,
+                escrow: escrow,
+                vault: vault,
+                associated_token_program: asspciated_token_program,
+                token_program: token_program,
+                system_program: system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Make {
+                deposit: 10,
+                seed: 123u64,
+                receive: 10,
+            }
+            .data(),
+        };
+
+        // Create and send the transaction containing the "Make" instruction
+        let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+
+        // Send the transaction and capture the result
+        let tx1 = program.send_transaction(transaction).unwrap();
+
+        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
+        let maker_ata_a_data: spl_token::state::Account =
+            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
+        let maker_ata_a_balance_after_make: u64 = maker_ata_a_data.amount;
+
+        msg!(
+            "maker ata a balance after make {}",
+            maker_ata_a_balance_after_make
+        );
+
+        assert_eq!(maker_ata_a_balance_after_make, 1000000000 - 10);
+
+        // Create the "Make" instruction to deposit tokens into the escrow
+        let refund_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Refund {
+                maker: maker,
+                mint_a: mint_a,
+                maker_ata_a: maker_ata_a,
+                escrow: escrow,
+                vault: vault,
+                offer_book: None,
+                token_program: token_program,
+                system_program: system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Refund {}.data(),
+        };
+
+        // Create and send the transaction containing the "Make" instruction
+        let message = Message::new(&[refund_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        // Send the transaction and capture the result
+        let tx = program.send_transaction(transaction).unwrap();
+
+        // Log transaction details
+        msg!("\nRefund transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+        msg!("Tx Signature: {}", tx.signature);
+
+        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
+        let maker_ata_a_data: spl_token::state::Account =
+            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
+        let maker_ata_a_balance_after_refund: u64 = maker_ata_a_data.amount;
+
+        assert_eq!(maker_ata_a_balance_after_refund, 1000000000);
+    }
+
+    #[test]
+    fn test_make_with_interval() {
+        // Setup the test environment by initializing LiteSVM and creating a payer keypair
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        // // Get the maker's public key from the payer keypair
+        // let maker = payer.pubkey();
+
+        msg!("Mint A: {}\n", mint_a);
+
+        msg!("Mint B: {}\n", mint_b);
+
+        msg!("Maker ATA A: {}\n", maker_ata_a);
+
+        msg!("Escrow PDA: {}\n", escrow);
+
+        msg!("Vault PDA: {}\n", vault);
+
+        // msg!("payer balance {}\n", maker.)
+
+        // Define program IDs for associated token program, token program, and system program
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        // Mint 1,000 tokens (with 6 decimal places) of Mint A to the maker's associated token account
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        // Create the "Make" instruction to deposit tokens into the escrow
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker: maker,
+                mint_a: mint_a,
+                mint_b: mint_b,
+                maker_ata_a: maker_ata_a,
+                escrow: escrow,
+                vault: vault,
+                associated_token_program: asspciated_token_program,
+                token_program: token_program,
+                system_program: system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::MakeWithInterval {
+                seed: 123u64,
+                deposit: 10,
+                interval: 120,
+                receive: 10,
+            }
+            .data(),
+        };
+
+        // let clock_account = program.get_account(&Clock::id()).unwrap();
+        // let clock = Clock::try_from_slice(&clock_account.data).unwrap();
+        // let current_timestamp = clock.unix_timestamp;
+
+        // let clock = Clock::get();
+        // let current_time = clock.unwrap().unix_timestamp as u64;
+
+        // Create and send the transaction containing the "Make" instruction
         let message = Message::new(&[make_ix], Some(&payer.pubkey()));
         let recent_blockhash = program.latest_blockhash();
 
@@ -634,10 +1188,11 @@ mod tests {
 
         let escrow_account = program.get_account(&escrow).unwrap();
         let escrow_data =
-            crate::state::EscrowInterval::try_deserialize(&mut escrow_account.data.as_ref())
-                .unwrap();
+            crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
         assert_eq!(escrow_data.seed, 123u64);
-        // assert_eq!(escrow_data.interval, current_timestamp + 120);
+        let clock = program.get_sysvar::<Clock>();
+        assert_eq!(escrow_data.not_before, Some(clock.unix_timestamp + 120));
+        assert_eq!(escrow_data.expires_at, None);
         assert_eq!(escrow_data.maker, maker);
         assert_eq!(escrow_data.mint_a, mint_a);
         assert_eq!(escrow_data.mint_b, mint_b);
@@ -693,7 +1248,7 @@ mod tests {
         // Create the "Make" instruction to deposit tokens into the escrow
         let make_ix = Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::MakeInterval {
+            accounts: crate::accounts::Make {
                 maker: maker,
                 mint_a: mint_a,
                 mint_b: mint_b,
@@ -735,7 +1290,7 @@ mod tests {
         // Create the "Make" instruction to deposit tokens into the escrow
         let take_ix = Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::TakeInterval {
+            accounts: crate::accounts::Take {
                 taker: taker,
                 maker: maker,
                 mint_a: mint_a,
@@ -745,6 +1300,7 @@ mod tests {
                 maker_ata_b: maker_ata_b,
                 escrow: escrow,
                 vault: vault,
+                offer_book: None,
                 associated_token_program: asspciated_token_program,
                 token_program: token_program,
                 system_program: system_program,
@@ -840,7 +1396,7 @@ mod tests {
         // Create the "Make" instruction to deposit tokens into the escrow
         let make_ix = Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::MakeInterval {
+            accounts: crate::accounts::Make {
                 maker: maker,
                 mint_a: mint_a,
                 mint_b: mint_b,
@@ -889,12 +1445,13 @@ mod tests {
         // Create the "Make" instruction to deposit tokens into the escrow
         let refund_ix = Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::RefundInterval {
+            accounts: crate::accounts::Refund {
                 maker: maker,
                 mint_a: mint_a,
                 maker_ata_a: maker_ata_a,
                 escrow: escrow,
                 vault: vault,
+                offer_book: None,
                 token_program: token_program,
                 system_program: system_program,
             }
@@ -922,4 +1479,1421 @@ mod tests {
 
         assert_eq!(maker_ata_a_balance_after_refund, 1000000000);
     }
+    #[test]
+    fn test_make_with_interval_overflow() {
+        let (
+            mut program,
+            payer,
+            _payer2,
+            maker,
+            _taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            _maker_ata_b,
+            _taker_ata_a,
+            _taker_ata_b,
+        ) = setup();
+
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        // neither an interval past i64::MAX nor one that overflows the clock is accepted
+        for interval in [u64::MAX, i64::MAX as u64] {
+            let make_ix = Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::Make {
+                    maker,
+                    mint_a,
+                    mint_b,
+                    maker_ata_a,
+                    escrow,
+                    vault,
+                    associated_token_program: spl_associated_token_account::ID,
+                    token_program: TOKEN_PROGRAM_ID,
+                    system_program: SYSTEM_PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::MakeWithInterval {
+                    deposit: 10,
+                    seed: 123u64,
+                    interval,
+                    receive: 10,
+                }
+                .data(),
+            };
+
+            let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+            let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+            assert!(program.send_transaction(transaction).is_err());
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_interval_escrow() {
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            _maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        // an EscrowInterval as the program wrote it before the time window
+        let bump = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &123u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .1;
+        let mut data = hash(b"account:EscrowInterval").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&123u64.to_le_bytes());
+        data.extend_from_slice(&200u64.to_le_bytes());
+        data.extend_from_slice(maker.as_ref());
+        data.extend_from_slice(mint_a.as_ref());
+        data.extend_from_slice(mint_b.as_ref());
+        data.extend_from_slice(&10u64.to_le_bytes());
+        data.push(bump);
+        program
+            .set_account(
+                escrow,
+                Account {
+                    lamports: program.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: PROGRAM_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+
+        CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_a)
+            .owner(&escrow)
+            .send()
+            .unwrap();
+        MintTo::new(&mut program, &payer, &mint_a, &vault, 10)
+            .send()
+            .unwrap();
+
+        let migrate_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MigrateEscrow {
+                payer: taker,
+                escrow,
+                mint_a,
+                vault,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::MigrateEscrow {}.data(),
+        };
+        let message = Message::new(std::slice::from_ref(&migrate_ix), Some(&taker));
+        let transaction = Transaction::new(&[&payer2], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let escrow_account = program.get_account(&escrow).unwrap();
+        let escrow_data =
+            crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.maker, maker);
+        assert_eq!(escrow_data.receive, 10);
+        assert_eq!(escrow_data.deposit, 10);
+        assert_eq!(escrow_data.not_before, Some(200));
+        assert_eq!(escrow_data.expires_at, None);
+
+        // a migrated escrow is not migrated twice
+        program.expire_blockhash();
+        let message = Message::new(&[migrate_ix], Some(&taker));
+        let transaction = Transaction::new(&[&payer2], message, program.latest_blockhash());
+        assert!(program.send_transaction(transaction).is_err());
+
+        // the old take entry point reads the migrated escrow
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 10)
+            .send()
+            .unwrap();
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = 250;
+        program.set_sysvar::<Clock>(&clock);
+
+        let take_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::TakeWithInterval {}.data(),
+        };
+        let message = Message::new(&[take_ix], Some(&taker));
+        let transaction = Transaction::new(&[&payer2], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data =
+            spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 10);
+    }
+
+    #[test]
+    fn test_refund_expired() {
+        // Setup the test environment by initializing LiteSVM and creating a payer keypair
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        // Define program IDs for associated token program, token program, and system program
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        // Mint 1,000 tokens (with 6 decimal places) of Mint A to the maker's associated token account
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        // Mint 1,000 tokens (with 6 decimal places) of Mint B to the takers's associated token account
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
+
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = 100;
+        program.set_sysvar::<Clock>(&clock);
+
+        // The escrow can be taken between 200 and 300
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker,
+                mint_a,
+                mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::MakeWithWindow {
+                deposit: 10,
+                seed: 123u64,
+                receive: 10,
+                taker: None,
+                not_before: Some(200),
+                expires_at: Some(300),
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let take_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Take {}.data(),
+        };
+
+        let refund_expired_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::RefundExpired {
+                caller: taker,
+                maker,
+                mint_a,
+                maker_ata_a,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::RefundExpired {}.data(),
+        };
+
+        // Nobody but the maker can refund before the escrow expires
+        let message = Message::new(
+            std::slice::from_ref(&refund_expired_ix),
+            Some(&payer2.pubkey()),
+        );
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = 350;
+        program.set_sysvar::<Clock>(&clock);
+
+        // Taking after expiry fails
+        let message = Message::new(&[take_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        // Anyone can send the deposit back to the maker once expired
+        program.expire_blockhash();
+        let message = Message::new(&[refund_expired_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nRefund expired transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
+        let maker_ata_a_data =
+            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
+        assert_eq!(maker_ata_a_data.amount, 1000000000);
+
+        assert!(program
+            .get_account(&escrow)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    #[test]
+    fn test_take_token_2022() {
+        let Token2022Setup {
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        } = setup_token_2022(false, false);
+
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = spl_token_2022::ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker,
+                mint_a,
+                mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Make {
+                deposit: 10,
+                seed: 123u64,
+                receive: 10,
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let take_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Take {}.data(),
+        };
+
+        let message = Message::new(&[take_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nToken-2022 take transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(token_2022_amount(&program, &maker_ata_b), 10);
+        assert_eq!(token_2022_amount(&program, &taker_ata_a), 10);
+        assert!(program
+            .get_account(&vault)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    #[test]
+    fn test_take_transfer_fee() {
+        let Token2022Setup {
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        } = setup_token_2022(true, false);
+
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = spl_token_2022::ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker,
+                mint_a,
+                mint_b,
+                maker_ata_a,
+                escrow,
+                vault,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Make {
+                deposit: 1000000,
+                seed: 123u64,
+                receive: 1000000,
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        // The escrow records what actually reached the vault
+        let escrow_account = program.get_account(&escrow).unwrap();
+        let escrow_data =
+            crate::state::Escrow::try_deserialize(&mut escrow_account.data.as_ref()).unwrap();
+        assert_eq!(escrow_data.deposit, 990000);
+        assert_eq!(token_2022_amount(&program, &vault), 990000);
+
+        let take_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow,
+                vault,
+                offer_book: None,
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Take {}.data(),
+        };
+
+        let message = Message::new(&[take_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nTransfer fee take transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // The taker covers the fee so the maker gets exactly what was asked
+        assert_eq!(token_2022_amount(&program, &maker_ata_b), 1000000);
+        assert_eq!(token_2022_amount(&program, &taker_ata_a), 980100);
+
+        // The withheld fees were harvested so the vault could be closed
+        assert!(program
+            .get_account(&vault)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    #[test]
+    fn test_take_transfer_hook() {
+        let Token2022Setup {
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        } = setup_token_2022(false, true);
+
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = spl_token_2022::ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        whitelist(&mut program, &maker);
+
+        let mut make_accounts = crate::accounts::Make {
+            maker,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            escrow,
+            vault,
+            associated_token_program: asspciated_token_program,
+            token_program,
+            system_program,
+        }
+        .to_account_metas(None);
+        make_accounts.extend(hook_accounts(&mint_a, &[maker]));
+
+        let make_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: make_accounts,
+            data: crate::instruction::Make {
+                deposit: 10,
+                seed: 123u64,
+                receive: 10,
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[make_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        // The vault is owned by the escrow, so its transfers check the escrow whitelist entry
+        let mut take_accounts = crate::accounts::Take {
+            taker,
+            maker,
+            mint_a,
+            mint_b,
+            taker_ata_a,
+            taker_ata_b,
+            maker_ata_b,
+            escrow,
+            vault,
+            offer_book: None,
+            associated_token_program: asspciated_token_program,
+            token_program,
+            system_program,
+        }
+        .to_account_metas(None);
+        take_accounts.extend(hook_accounts(&mint_a, &[escrow]));
+
+        let take_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: take_accounts,
+            data: crate::instruction::Take {}.data(),
+        };
+
+        // The hook rejects the transfer until the escrow is whitelisted
+        let message = Message::new(std::slice::from_ref(&take_ix), Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        whitelist(&mut program, &escrow);
+
+        program.expire_blockhash();
+        let message = Message::new(&[take_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nTransfer hook take transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        assert_eq!(token_2022_amount(&program, &maker_ata_b), 10);
+        assert_eq!(token_2022_amount(&program, &taker_ata_a), 10);
+    }
+
+    // Maker offers 10 of mint A and a one-of-one NFT for 10 of mint B
+    fn make_basket(
+        program: &mut LiteSVM,
+        payer: &Keypair,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        maker_ata_a: Pubkey,
+    ) -> (Pubkey, Pubkey, Pubkey) {
+        let maker = payer.pubkey();
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        let nft = CreateMint::new(program, payer)
+            .decimals(0)
+            .authority(&maker)
+            .send()
+            .unwrap();
+        let maker_ata_nft = CreateAssociatedTokenAccount::new(program, payer, &nft)
+            .owner(&maker)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, &nft, &maker_ata_nft, 1)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        let basket = Pubkey::find_program_address(
+            &[b"basket", maker.as_ref(), &123u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        let make_basket_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MakeBasket {
+                maker,
+                basket,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::MakeBasket {
+                seed: 123u64,
+                asked: vec![crate::state::BasketItem {
+                    mint: mint_b,
+                    amount: 10,
+                }],
+                taker: None,
+            }
+            .data(),
+        };
+
+        let mut instructions = vec![make_basket_ix];
+        for (index, (mint, maker_ata, amount)) in
+            [(mint_a, maker_ata_a, 10), (nft, maker_ata_nft, 1)]
+                .into_iter()
+                .enumerate()
+        {
+            let vault = Pubkey::find_program_address(
+                &[b"basket_vault", basket.as_ref(), &[index as u8]],
+                &PROGRAM_ID,
+            )
+            .0;
+
+            instructions.push(Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::DepositBasket {
+                    maker,
+                    mint,
+                    maker_ata,
+                    basket,
+                    vault,
+                    token_program,
+                    system_program,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::DepositBasket { amount }.data(),
+            });
+        }
+
+        let message = Message::new(&instructions, Some(&maker));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        (basket, nft, maker_ata_nft)
+    }
+
+    fn basket_vault(basket: &Pubkey, index: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"basket_vault", basket.as_ref(), &[index]], &PROGRAM_ID).0
+    }
+
+    #[test]
+    fn test_take_basket() {
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            _escrow,
+            _vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        let (basket, nft, _) = make_basket(&mut program, &payer, mint_a, mint_b, maker_ata_a);
+
+        let basket_account = program.get_account(&basket).unwrap();
+        let basket_data =
+            crate::state::Basket::try_deserialize(&mut basket_account.data.as_ref()).unwrap();
+        assert_eq!(basket_data.offered.len(), 2);
+        assert_eq!(basket_data.offered[1].mint, nft);
+
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
+        let taker_ata_nft = CreateAssociatedTokenAccount::new(&mut program, &payer2, &nft)
+            .owner(&taker)
+            .send()
+            .unwrap();
+
+        let mut accounts = crate::accounts::TakeBasket {
+            taker,
+            maker,
+            basket,
+            system_program,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            // asked: mint B from the taker to the maker
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(taker_ata_b, false),
+            AccountMeta::new(maker_ata_b, false),
+            AccountMeta::new_readonly(token_program, false),
+            // offered: mint A and the NFT from their vaults to the taker
+            AccountMeta::new(mint_a, false),
+            AccountMeta::new(basket_vault(&basket, 0), false),
+            AccountMeta::new(taker_ata_a, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new(nft, false),
+            AccountMeta::new(basket_vault(&basket, 1), false),
+            AccountMeta::new(taker_ata_nft, false),
+            AccountMeta::new_readonly(token_program, false),
+        ]);
+
+        let take_basket_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: crate::instruction::TakeBasket {}.data(),
+        };
+
+        let message = Message::new(&[take_basket_ix], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nTake basket transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let maker_ata_b_account = program.get_account(&maker_ata_b).unwrap();
+        let maker_ata_b_data =
+            spl_token::state::Account::unpack(&maker_ata_b_account.data).unwrap();
+        assert_eq!(maker_ata_b_data.amount, 10);
+
+        let taker_ata_a_account = program.get_account(&taker_ata_a).unwrap();
+        let taker_ata_a_data =
+            spl_token::state::Account::unpack(&taker_ata_a_account.data).unwrap();
+        assert_eq!(taker_ata_a_data.amount, 10);
+
+        let taker_ata_nft_account = program.get_account(&taker_ata_nft).unwrap();
+        let taker_ata_nft_data =
+            spl_token::state::Account::unpack(&taker_ata_nft_account.data).unwrap();
+        assert_eq!(taker_ata_nft_data.amount, 1);
+
+        for account in [basket, basket_vault(&basket, 0), basket_vault(&basket, 1)] {
+            assert!(program
+                .get_account(&account)
+                .is_none_or(|account| account.lamports == 0));
+        }
+    }
+
+    #[test]
+    fn test_refund_basket() {
+        let (
+            mut program,
+            payer,
+            _payer2,
+            maker,
+            _taker,
+            mint_a,
+            mint_b,
+            _escrow,
+            _vault,
+            maker_ata_a,
+            _maker_ata_b,
+            _taker_ata_a,
+            _taker_ata_b,
+        ) = setup();
+
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        let (basket, nft, maker_ata_nft) =
+            make_basket(&mut program, &payer, mint_a, mint_b, maker_ata_a);
+
+        let mut accounts = crate::accounts::RefundBasket {
+            maker,
+            basket,
+            system_program,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            AccountMeta::new(mint_a, false),
+            AccountMeta::new(basket_vault(&basket, 0), false),
+            AccountMeta::new(maker_ata_a, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new(nft, false),
+            AccountMeta::new(basket_vault(&basket, 1), false),
+            AccountMeta::new(maker_ata_nft, false),
+            AccountMeta::new_readonly(token_program, false),
+        ]);
+
+        let refund_basket_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: crate::instruction::RefundBasket {}.data(),
+        };
+
+        let message = Message::new(&[refund_basket_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nRefund basket transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let maker_ata_a_account = program.get_account(&maker_ata_a).unwrap();
+        let maker_ata_a_data =
+            spl_token::state::Account::unpack(&maker_ata_a_account.data).unwrap();
+        assert_eq!(maker_ata_a_data.amount, 1000000000);
+
+        let maker_ata_nft_account = program.get_account(&maker_ata_nft).unwrap();
+        let maker_ata_nft_data =
+            spl_token::state::Account::unpack(&maker_ata_nft_account.data).unwrap();
+        assert_eq!(maker_ata_nft_data.amount, 1);
+
+        assert!(program
+            .get_account(&basket)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    #[test]
+    fn test_take_basket_transfer_hook() {
+        let Token2022Setup {
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+            ..
+        } = setup_token_2022(false, true);
+
+        let token_program = spl_token_2022::ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        whitelist(&mut program, &maker);
+
+        let basket = Pubkey::find_program_address(
+            &[b"basket", maker.as_ref(), &123u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+        whitelist(&mut program, &basket);
+
+        let make_basket_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MakeBasket {
+                maker,
+                basket,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::MakeBasket {
+                seed: 123u64,
+                asked: vec![crate::state::BasketItem {
+                    mint: mint_b,
+                    amount: 10,
+                }],
+                taker: None,
+            }
+            .data(),
+        };
+        let mut deposit_accounts = crate::accounts::DepositBasket {
+            maker,
+            mint: mint_a,
+            maker_ata: maker_ata_a,
+            basket,
+            vault: basket_vault(&basket, 0),
+            token_program,
+            system_program,
+        }
+        .to_account_metas(None);
+        deposit_accounts.extend(hook_accounts(&mint_a, &[maker]));
+        let deposit_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: deposit_accounts,
+            data: crate::instruction::DepositBasket { amount: 10 }.data(),
+        };
+
+        let message = Message::new(&[make_basket_ix, deposit_ix], Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        // the vault is owned by the basket, so its transfer checks the basket whitelist entry
+        let mut accounts = crate::accounts::TakeBasket {
+            taker,
+            maker,
+            basket,
+            system_program,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(taker_ata_b, false),
+            AccountMeta::new(maker_ata_b, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new(mint_a, false),
+            AccountMeta::new(basket_vault(&basket, 0), false),
+            AccountMeta::new(taker_ata_a, false),
+            AccountMeta::new_readonly(token_program, false),
+        ]);
+        accounts.extend(hook_accounts(&mint_a, &[basket]));
+
+        let take_basket_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: crate::instruction::TakeBasket {}.data(),
+        };
+
+        let message = Message::new(&[take_basket_ix], Some(&taker));
+        let transaction = Transaction::new(&[&payer2], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_2022_amount(&program, &maker_ata_b), 10);
+        assert_eq!(token_2022_amount(&program, &taker_ata_a), 10);
+        assert!(program
+            .get_account(&basket)
+            .is_none_or(|account| account.lamports == 0));
+    }
+
+    fn offer_book(program: &LiteSVM, offer_book: &Pubkey) -> crate::state::OfferBook {
+        let offer_book_account = program.get_account(offer_book).unwrap();
+        crate::state::OfferBook::try_deserialize(&mut offer_book_account.data.as_ref()).unwrap()
+    }
+
+    // Makes an escrow depositing `deposit` of mint A and lists it on the offer book of its pair
+    fn make_and_list_ixs(
+        maker: Pubkey,
+        (mint_a, mint_b): (Pubkey, Pubkey),
+        maker_ata_a: Pubkey,
+        seed: u64,
+        (deposit, receive): (u64, u64),
+        expires_at: Option<i64>,
+    ) -> [Instruction; 2] {
+        let escrow = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let offer_book = Pubkey::find_program_address(
+            &[b"offer_book", mint_a.as_ref(), mint_b.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        [
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::Make {
+                    maker,
+                    mint_a,
+                    mint_b,
+                    maker_ata_a,
+                    escrow,
+                    vault: associated_token::get_associated_token_address(&escrow, &mint_a),
+                    associated_token_program: spl_associated_token_account::ID,
+                    token_program: TOKEN_PROGRAM_ID,
+                    system_program: SYSTEM_PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::MakeWithWindow {
+                    seed,
+                    deposit,
+                    receive,
+                    taker: None,
+                    not_before: None,
+                    expires_at,
+                }
+                .data(),
+            },
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::ListOffer {
+                    maker,
+                    escrow,
+                    offer_book,
+                    system_program: SYSTEM_PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::ListOffer {}.data(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_offer_book() {
+        let (
+            mut program,
+            payer,
+            payer2,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        ) = setup();
+
+        let asspciated_token_program = spl_associated_token_account::ID;
+        let token_program = TOKEN_PROGRAM_ID;
+        let system_program = SYSTEM_PROGRAM_ID;
+
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+        MintTo::new(&mut program, &payer, &mint_b, &taker_ata_b, 1000000000)
+            .send()
+            .unwrap();
+
+        let escrow2 = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &456u64.to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let vault2 = associated_token::get_associated_token_address(&escrow2, &mint_a);
+
+        let offer_book_pda = Pubkey::find_program_address(
+            &[b"offer_book", mint_a.as_ref(), mint_b.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        // Two escrows of the same pair, the second one asks twice as much
+        let mut instructions = vec![];
+        for (seed, escrow, vault, receive) in
+            [(123u64, escrow, vault, 10), (456u64, escrow2, vault2, 20)]
+        {
+            instructions.push(Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::Make {
+                    maker,
+                    mint_a,
+                    mint_b,
+                    maker_ata_a,
+                    escrow,
+                    vault,
+                    associated_token_program: asspciated_token_program,
+                    token_program,
+                    system_program,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::Make {
+                    deposit: 1_000,
+                    seed,
+                    receive,
+                }
+                .data(),
+            });
+            instructions.push(Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::ListOffer {
+                    maker,
+                    escrow,
+                    offer_book: offer_book_pda,
+                    system_program,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::ListOffer {}.data(),
+            });
+        }
+
+        let message = Message::new(&instructions, Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.offers.len(), 2);
+        assert_eq!(book.best(now).unwrap().escrow, escrow);
+
+        // Repricing the second escrow below the first moves it to the top of the book
+        let update_terms_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateTerms {
+                maker,
+                escrow: escrow2,
+                offer_book: Some(offer_book_pda),
+            }
+            .to_account_metas(None),
+            data: crate::instruction::UpdateTerms {
+                receive: 5,
+                expires_at: None,
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[update_terms_ix], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let escrow2_account = program.get_account(&escrow2).unwrap();
+        let escrow2_data =
+            crate::state::Escrow::try_deserialize(&mut escrow2_account.data.as_ref()).unwrap();
+        assert_eq!(escrow2_data.receive, 5);
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.best(now).unwrap().escrow, escrow2);
+        assert_eq!(book.best(now).unwrap().receive, 5);
+
+        // The taker fills the best offer found on-chain
+        let best = book.best(now).unwrap().escrow;
+        let best_vault = associated_token::get_associated_token_address(&best, &mint_a);
+        let take_ix = |expected_receive: u64| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker,
+                maker,
+                mint_a,
+                mint_b,
+                taker_ata_a,
+                taker_ata_b,
+                maker_ata_b,
+                escrow: best,
+                vault: best_vault,
+                offer_book: Some(offer_book_pda),
+                associated_token_program: asspciated_token_program,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::TakeChecked { expected_receive }.data(),
+        };
+
+        // The taker's price no longer matches the repriced escrow
+        let message = Message::new(&[take_ix(4)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        let message = Message::new(&[take_ix(5)], Some(&payer2.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer2], message, recent_blockhash);
+        let tx = program.send_transaction(transaction).unwrap();
+
+        msg!("\nTake best offer transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let maker_ata_b_account = program.get_account(&maker_ata_b).unwrap();
+        let maker_ata_b_data =
+            spl_token::state::Account::unpack(&maker_ata_b_account.data).unwrap();
+        assert_eq!(maker_ata_b_data.amount, 5);
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.offers.len(), 1);
+        assert_eq!(book.best(now).unwrap().escrow, escrow);
+
+        // A listed escrow cannot be refunded without removing it from the book
+        let refund_ix = |offer_book| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Refund {
+                maker,
+                mint_a,
+                maker_ata_a,
+                escrow,
+                vault,
+                offer_book,
+                token_program,
+                system_program,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Refund {}.data(),
+        };
+
+        let message = Message::new(&[refund_ix(None)], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        assert!(program.send_transaction(transaction).is_err());
+
+        let message = Message::new(&[refund_ix(Some(offer_book_pda))], Some(&payer.pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+        program.send_transaction(transaction).unwrap();
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert!(book.offers.is_empty());
+    }
+
+    #[test]
+    fn test_offer_book_full() {
+        let (mut program, payer, _, maker, _, mint_a, mint_b, _, _, maker_ata_a, _, _, _) = setup();
+        let min_deposit = crate::state::OfferBook::MIN_OFFER_DEPOSIT;
+
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        let offer_book_pda = Pubkey::find_program_address(
+            &[b"offer_book", mint_a.as_ref(), mint_b.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        // Dust offers are not listed
+        let dust = make_and_list_ixs(
+            maker,
+            (mint_a, mint_b),
+            maker_ata_a,
+            0,
+            (min_deposit - 1, 1),
+            None,
+        );
+        let message = Message::new(&dust, Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        assert!(program.send_transaction(transaction).is_err());
+
+        // Fill the book, the offer of seed n asks for 10 + n mint B
+        let max_offers = crate::state::OfferBook::MAX_OFFERS as u64;
+        for seed in (0..max_offers).step_by(2) {
+            let instructions: Vec<Instruction> = (seed..seed + 2)
+                .flat_map(|seed| {
+                    make_and_list_ixs(
+                        maker,
+                        (mint_a, mint_b),
+                        maker_ata_a,
+                        seed,
+                        (min_deposit, 10 + seed),
+                        None,
+                    )
+                })
+                .collect();
+            let message = Message::new(&instructions, Some(&maker));
+            let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+            program.send_transaction(transaction).unwrap();
+        }
+
+        let now = program.get_sysvar::<Clock>().unix_timestamp;
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.offers.len(), crate::state::OfferBook::MAX_OFFERS);
+
+        // An offer no cheaper than the worst listed one is turned away
+        let expensive = make_and_list_ixs(
+            maker,
+            (mint_a, mint_b),
+            maker_ata_a,
+            max_offers,
+            (min_deposit, 10 + max_offers),
+            None,
+        );
+        let message = Message::new(&expensive, Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        assert!(program.send_transaction(transaction).is_err());
+
+        // A cheaper offer takes the place of the worst one
+        let cheap = make_and_list_ixs(
+            maker,
+            (mint_a, mint_b),
+            maker_ata_a,
+            max_offers,
+            (min_deposit, 1),
+            None,
+        );
+        let message = Message::new(&cheap, Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let worst = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &(max_offers - 1).to_le_bytes()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.offers.len(), crate::state::OfferBook::MAX_OFFERS);
+        assert_eq!(book.best(now).unwrap().receive, 1);
+        assert!(book.offers.iter().all(|offer| offer.escrow != worst));
+
+        // The evicted escrow can still be repriced, it is just no longer listed
+        let update_terms_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateTerms {
+                maker,
+                escrow: worst,
+                offer_book: Some(offer_book_pda),
+            }
+            .to_account_metas(None),
+            data: crate::instruction::UpdateTerms {
+                receive: 5,
+                expires_at: None,
+            }
+            .data(),
+        };
+        let message = Message::new(&[update_terms_ix], Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let worst_account = program.get_account(&worst).unwrap();
+        let worst_data =
+            crate::state::Escrow::try_deserialize(&mut worst_account.data.as_ref()).unwrap();
+        assert_eq!(worst_data.receive, 5);
+        assert!(!worst_data.listed);
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert!(book.offers.iter().all(|offer| offer.escrow != worst));
+    }
+
+    #[test]
+    fn test_offer_book_expired_best() {
+        let (mut program, payer, _, maker, _, mint_a, mint_b, _, _, maker_ata_a, _, _, _) = setup();
+        let min_deposit = crate::state::OfferBook::MIN_OFFER_DEPOSIT;
+
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000)
+            .send()
+            .unwrap();
+
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = 100;
+        program.set_sysvar::<Clock>(&clock);
+
+        let offer_book_pda = Pubkey::find_program_address(
+            &[b"offer_book", mint_a.as_ref(), mint_b.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let escrow = |seed: u64| {
+            Pubkey::find_program_address(
+                &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+                &PROGRAM_ID,
+            )
+            .0
+        };
+
+        // The cheapest offer expires at 200
+        let mut instructions = vec![];
+        for (seed, receive, expires_at) in [(1u64, 5, Some(200)), (2u64, 10, None)] {
+            instructions.extend(make_and_list_ixs(
+                maker,
+                (mint_a, mint_b),
+                maker_ata_a,
+                seed,
+                (min_deposit, receive),
+                expires_at,
+            ));
+        }
+
+        let message = Message::new(&instructions, Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.best(150).unwrap().escrow, escrow(1));
+
+        // Once expired it is skipped by the book's best offer
+        clock.unix_timestamp = 250;
+        program.set_sysvar::<Clock>(&clock);
+        assert_eq!(book.best(250).unwrap().escrow, escrow(2));
+
+        // and dropped by the next listing
+        let instructions = make_and_list_ixs(
+            maker,
+            (mint_a, mint_b),
+            maker_ata_a,
+            3,
+            (min_deposit, 20),
+            None,
+        );
+        let message = Message::new(&instructions, Some(&maker));
+        let transaction = Transaction::new(&[&payer], message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+
+        let book = offer_book(&program, &offer_book_pda);
+        assert_eq!(book.offers.len(), 2);
+        assert!(book.offers.iter().all(|offer| offer.escrow != escrow(1)));
+        assert_eq!(book.best(250).unwrap().escrow, escrow(2));
+    }
 }
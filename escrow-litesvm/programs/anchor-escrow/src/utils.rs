@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        extension::transfer_fee::TransferFeeConfig, onchain::invoke_transfer_checked,
+    },
+    token_interface::{
+        close_account, get_mint_extension_data, harvest_withheld_tokens_to_mint, CloseAccount,
+        HarvestWithheldTokensToMint, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::error::EscrowError;
+use crate::state::{Basket, BasketItem, Escrow, Offer, OfferBook};
+
+// Same as `transfer_checked`, but resolves the extra accounts of transfer hook
+// mints from the remaining accounts of the context
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+// Amount to send so that `amount` arrives once the mint transfer fee is withheld
+pub fn amount_with_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let Ok(fee_config) = get_mint_extension_data::<TransferFeeConfig>(&mint.to_account_info())
+    else {
+        return Ok(amount);
+    };
+
+    fee_config
+        .get_epoch_fee(Clock::get()?.epoch)
+        .calculate_pre_fee_amount(amount)
+        .ok_or(EscrowError::InvalidAmount.into())
+}
+
+// Token accounts holding withheld transfer fees cannot be closed, move the fees to the mint first
+pub fn harvest_fees<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    account: AccountInfo<'info>,
+) -> Result<()> {
+    if get_mint_extension_data::<TransferFeeConfig>(&mint.to_account_info()).is_err() {
+        return Ok(());
+    }
+
+    let cpi_accounts = HarvestWithheldTokensToMint {
+        token_program_id: token_program.to_account_info(),
+        mint: mint.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+    harvest_withheld_tokens_to_mint(cpi_ctx, vec![account])
+}
+
+// Empties the basket vault of `item` into the given token account and closes it.
+// `accounts` are the [mint, vault, to, token_program] of the item, in that order, and
+// `hook_accounts` the extra accounts of any transfer hook mints in the basket
+pub fn withdraw_basket_vault<'info>(
+    basket: &Account<'info, Basket>,
+    index: usize,
+    item: &BasketItem,
+    accounts: &'info [AccountInfo<'info>],
+    hook_accounts: &'info [AccountInfo<'info>],
+    rent_receiver: AccountInfo<'info>,
+) -> Result<()> {
+    let [mint, vault, to, token_program] = accounts else {
+        return err!(EscrowError::InvalidBasketAccounts);
+    };
+
+    let (vault_key, _) = Pubkey::find_program_address(
+        &[b"basket_vault", basket.key().as_ref(), &[index as u8]],
+        &crate::ID,
+    );
+    require_keys_eq!(mint.key(), item.mint, EscrowError::InvalidBasketAccounts);
+    require_keys_eq!(vault.key(), vault_key, EscrowError::InvalidBasketAccounts);
+
+    let mint = InterfaceAccount::<Mint>::try_from(mint)?;
+    let vault = InterfaceAccount::<TokenAccount>::try_from(vault)?;
+    let token_program = Interface::<TokenInterface>::try_from(token_program)?;
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"basket",
+        basket.maker.as_ref(),
+        &basket.seed.to_le_bytes()[..],
+        &[basket.bump],
+    ]];
+
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        to: to.to_account_info(),
+        mint: mint.to_account_info(),
+        authority: basket.to_account_info(),
+    };
+
+    let cpi_context =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, &signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+    transfer_checked_with_hook(cpi_context, vault.amount, mint.decimals)?;
+
+    harvest_fees(&token_program, &mint, vault.to_account_info())?;
+
+    let cpi_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_receiver,
+        authority: basket.to_account_info(),
+    };
+
+    let cpi_context =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, &signer_seeds);
+
+    close_account(cpi_context)
+}
+
+// Keeps the offer book entry of a listed escrow in line with its terms, `open` is false once the
+// escrow is closed. An escrow drained below the minimum offer size, or already dropped from the
+// book as the worst offer of a full book or once expired, is no longer listed
+pub fn sync_offer_book(
+    escrow: &mut Account<Escrow>,
+    offer_book: Option<&mut Account<OfferBook>>,
+    open: bool,
+) -> Result<()> {
+    if !escrow.listed {
+        return Ok(());
+    }
+
+    let offer_book = offer_book.ok_or(EscrowError::OfferBookMissing)?;
+    let in_book = offer_book.remove(&escrow.key());
+
+    if open && in_book && escrow.deposit >= OfferBook::MIN_OFFER_DEPOSIT {
+        offer_book.insert(
+            Offer {
+                escrow: escrow.key(),
+                deposit: escrow.deposit,
+                receive: escrow.receive,
+                expires_at: escrow.expires_at,
+            },
+            Clock::get()?.unix_timestamp,
+        )?;
+    } else {
+        escrow.listed = false;
+    }
+
+    Ok(())
+}
@@ -2,3 +2,16 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Marketplace fees are expressed in basis points of the sale price
+#[constant]
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+// Metaplex Core, whose assets are single accounts rather than SPL mints
+#[constant]
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+// Listings handled by one `batch_list` or `sweep_purchase`, keeping it within compute and
+// transaction size limits
+#[constant]
+pub const MAX_BATCH_LEN: usize = 5;
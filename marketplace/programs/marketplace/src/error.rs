@@ -8,6 +8,74 @@ pub enum ErrorCode {
 
 #[error_code]
 pub enum MarketplaceError {
-    #[msg("Custom error message")]
-    CustomError1,
+    #[msg("Marketplace name must be between 1 and 32 bytes")]
+    InvalidName,
+    #[msg("Fee must be at most 10000 basis points")]
+    InvalidFee,
+    #[msg("Listing price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Amount exceeds the withdrawable treasury balance")]
+    InsufficientTreasury,
+    #[msg("Marketplace fee and royalties exceed the sale price")]
+    FeesExceedPrice,
+    #[msg("A verified creator account is missing from the remaining accounts")]
+    MissingCreatorAccount,
+    #[msg("Remaining account does not match the verified creator")]
+    InvalidCreatorAccount,
+    #[msg("NFT is not part of a verified collection allowed on this marketplace")]
+    CollectionNotAllowed,
+    #[msg("Marketplace already allows the maximum number of collections")]
+    TooManyCollections,
+    #[msg("Collection is already allowed")]
+    CollectionAlreadyAllowed,
+    #[msg("Collection is not allowed")]
+    CollectionNotFound,
+    #[msg("Payment mint is not allowed on this marketplace")]
+    PaymentMintNotAllowed,
+    #[msg("Marketplace already allows the maximum number of payment mints")]
+    TooManyPaymentMints,
+    #[msg("Payment mint is already allowed")]
+    PaymentMintAlreadyAllowed,
+    #[msg("Payment mint does not match the listing")]
+    PaymentMintMismatch,
+    #[msg("Token-priced listings need the payment mint and token accounts")]
+    MissingPaymentAccounts,
+    #[msg("Offer expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("The NFT must come from either the seller's token account or the listing vault")]
+    MissingNftSource,
+    #[msg("Auction parameters are invalid")]
+    InvalidAuction,
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+    #[msg("Bid is below the minimum accepted bid")]
+    BidTooLow,
+    #[msg("The outbid bidder's account is missing or does not match")]
+    InvalidPreviousBidder,
+    #[msg("The NFT must go to the winning bidder, or back to the seller without bids")]
+    InvalidAuctionRecipient,
+    #[msg("Accounts for the listing's custody mode are missing")]
+    MissingListingAccounts,
+    #[msg("Account is not a Metaplex Core asset")]
+    InvalidCoreAsset,
+    #[msg("The asset's collection account is missing or does not match")]
+    InvalidCoreCollection,
+    #[msg("Seller does not own the asset")]
+    NotAssetOwner,
+    #[msg("Batches hold between 1 and MAX_BATCH_LEN listings")]
+    BatchTooLarge,
+    #[msg("Remaining accounts do not match the batch")]
+    InvalidBatchAccounts,
+    #[msg("Sweep total exceeds the maximum price")]
+    SweepPriceExceeded,
+    #[msg("Only custodial listings priced in SOL can be swept")]
+    UnsupportedListing,
 }
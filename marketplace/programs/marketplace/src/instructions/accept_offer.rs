@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{MasterEditionAccount, Metadata, MetadataAccount};
+use anchor_spl::token::{
+    close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+};
+
+use crate::error::*;
+use crate::state::{Listing, Marketplace, Offer};
+use crate::utils::{basis_points, pay_royalties_from_escrow, royalties_due, thaw_nft};
+
+// The NFT comes out of the listing vault when it is listed with custody, otherwise out of the
+// seller's account
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[mut]]
+    pub buyer: SystemAccount<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program = token_program,
+    ]]
+    pub mint: Account<'info, Mint>,
+    #[account[
+      mut,
+      has_one = buyer,
+      has_one = mint,
+      seeds = [b"offer", marketplace.key().as_ref(), mint.key().as_ref(), buyer.key().as_ref()],
+      bump = offer.bump,
+      close = buyer
+    ]]
+    pub offer: Account<'info, Offer>,
+    #[account[
+      mut,
+      associated_token::mint = mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Option<Box<Account<'info, TokenAccount>>>,
+    #[account[
+      mut,
+      has_one = mint,
+      has_one = seller,
+      seeds = [marketplace.key().as_ref(), mint.key().as_ref()],
+      bump = listing.bump,
+      close = seller
+    ]]
+    pub listing: Option<Box<Account<'info, Listing>>>,
+    #[account[
+      mut,
+      associated_token::mint = mint,
+      associated_token::authority = listing,
+    ]]
+    pub vault: Option<Box<Account<'info, TokenAccount>>>,
+    // only needed to thaw the seller's account for a delegated listing
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    // the seller is the only signer, so they open the buyer's account if it is missing
+    #[account[
+      init_if_needed,
+      payer = seller,
+      associated_token::mint = mint,
+      associated_token::authority = buyer,
+    ]]
+    pub buyer_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+      constraint = marketplace.accepts(&metadata) @ MarketplaceError::CollectionNotAllowed
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> AcceptOffer<'info> {
+    // Splits the escrowed amount between the seller, the treasury and the verified creators
+    pub fn accept_offer(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            !self.offer.is_expired(Clock::get()?.unix_timestamp),
+            MarketplaceError::OfferExpired
+        );
+
+        let amount = self.offer.amount;
+        let fees = basis_points(amount, self.marketplace.fees)?;
+        let royalties = royalties_due(
+            &self.marketplace,
+            &self.metadata,
+            amount,
+            !creators.is_empty(),
+        )?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let amount_to_be_sent = amount
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        // the offer is owned by this program, so its lamports move without a system transfer
+        self.offer.sub_lamports(amount_to_be_sent)?;
+        self.seller.add_lamports(amount_to_be_sent)?;
+        self.offer.sub_lamports(fees)?;
+        self.treasury.add_lamports(fees)?;
+
+        pay_royalties_from_escrow(&self.offer.to_account_info(), &royalties, creators)?;
+
+        Ok(())
+    }
+
+    pub fn transfer_nft(&mut self) -> Result<()> {
+        match (&self.listing, &self.vault) {
+            (Some(listing), Some(vault)) if !listing.delegated => {
+                let mkey = self.marketplace.key();
+                let mint_key = self.mint.key();
+                let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[listing.bump]];
+                let s = [seeds];
+
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault.to_account_info(),
+                        to: self.buyer_ata.to_account_info(),
+                        authority: listing.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                    },
+                    &s,
+                );
+                transfer_checked(cpi_ctx, 1, 0)?;
+
+                // the seller paid for the vault, so its rent goes back to them
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: vault.to_account_info(),
+                        destination: self.seller.to_account_info(),
+                        authority: listing.to_account_info(),
+                    },
+                    &s,
+                );
+                close_account(cpi_ctx)
+            }
+            (Some(listing), None) if listing.delegated => {
+                let (Some(seller_ata), Some(edition)) = (&self.seller_ata, &self.edition) else {
+                    return err!(MarketplaceError::MissingListingAccounts);
+                };
+
+                let mkey = self.marketplace.key();
+                let mint_key = self.mint.key();
+                let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[listing.bump]];
+                let s = [seeds];
+
+                thaw_nft(
+                    &self.metadata_program.to_account_info(),
+                    &listing.to_account_info(),
+                    &seller_ata.to_account_info(),
+                    &edition.to_account_info(),
+                    &self.mint.to_account_info(),
+                    &self.token_program.to_account_info(),
+                    &s,
+                )?;
+
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: seller_ata.to_account_info(),
+                        to: self.buyer_ata.to_account_info(),
+                        authority: listing.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                    },
+                    &s,
+                );
+                transfer_checked(cpi_ctx, 1, 0)
+            }
+            (None, None) => {
+                let seller_ata = self
+                    .seller_ata
+                    .as_ref()
+                    .ok_or(MarketplaceError::MissingNftSource)?;
+
+                let cpi_ctx = CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: seller_ata.to_account_info(),
+                        to: self.buyer_ata.to_account_info(),
+                        authority: self.seller.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                    },
+                );
+                transfer_checked(cpi_ctx, 1, 0)
+            }
+            _ => err!(MarketplaceError::MissingNftSource),
+        }
+    }
+}
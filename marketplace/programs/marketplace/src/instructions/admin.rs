@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::constants::MAX_FEE_BPS;
+use crate::error::*;
+use crate::state::Marketplace;
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account[mut]]
+    pub admin: Signer<'info>,
+    #[account[
+      has_one = admin,
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    // The treasury keeps its rent-exempt minimum so it can go on collecting fees
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        let withdrawable = self
+            .treasury
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(
+            amount <= withdrawable,
+            MarketplaceError::InsufficientTreasury
+        );
+
+        let mkey = self.marketplace.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            mkey.as_ref(),
+            &[self.marketplace.treasury_bump],
+        ];
+        let s = [seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.treasury.to_account_info(),
+                to: self.admin.to_account_info(),
+            },
+            &s,
+        );
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct AdminUpdate<'info> {
+    pub admin: Signer<'info>,
+    #[account[
+      mut,
+      has_one = admin,
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+}
+
+impl<'info> AdminUpdate<'info> {
+    pub fn update_fee(&mut self, fee: u16) -> Result<()> {
+        require!(fee <= MAX_FEE_BPS, MarketplaceError::InvalidFee);
+
+        self.marketplace.fees = fee;
+        Ok(())
+    }
+
+    pub fn set_royalty_enforcement(&mut self, enforce: bool) -> Result<()> {
+        self.marketplace.enforce_royalties = enforce;
+        Ok(())
+    }
+
+    pub fn set_rewards(
+        &mut self,
+        reward_rate_bps: u16,
+        discount_threshold: u64,
+        fee_discount_bps: u16,
+    ) -> Result<()> {
+        require!(
+            fee_discount_bps <= MAX_FEE_BPS,
+            MarketplaceError::InvalidFee
+        );
+
+        self.marketplace.reward_rate_bps = reward_rate_bps;
+        self.marketplace.discount_threshold = discount_threshold;
+        self.marketplace.fee_discount_bps = fee_discount_bps;
+        Ok(())
+    }
+
+    pub fn allow_collection(&mut self, collection: Pubkey) -> Result<()> {
+        let allowed = &mut self.marketplace.allowed_collections;
+        require!(
+            !allowed.contains(&collection),
+            MarketplaceError::CollectionAlreadyAllowed
+        );
+        require!(
+            allowed.len() < Marketplace::MAX_COLLECTIONS,
+            MarketplaceError::TooManyCollections
+        );
+
+        allowed.push(collection);
+        Ok(())
+    }
+
+    // Listings already priced in the mint can still be bought or unlisted
+    pub fn disallow_payment_mint(&mut self, payment_mint: Pubkey) -> Result<()> {
+        let allowed = &mut self.marketplace.payment_mints;
+        let index = allowed
+            .iter()
+            .position(|allowed| allowed.mint == payment_mint)
+            .ok_or(MarketplaceError::PaymentMintNotAllowed)?;
+
+        allowed.remove(index);
+        Ok(())
+    }
+
+    // Removing the last collection opens the marketplace to every mint again
+    pub fn disallow_collection(&mut self, collection: Pubkey) -> Result<()> {
+        let allowed = &mut self.marketplace.allowed_collections;
+        let index = allowed
+            .iter()
+            .position(|key| *key == collection)
+            .ok_or(MarketplaceError::CollectionNotFound)?;
+
+        allowed.remove(index);
+        Ok(())
+    }
+}
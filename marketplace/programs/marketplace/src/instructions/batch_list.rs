@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_BATCH_LEN;
+use crate::error::*;
+use crate::instructions::{List, ListBumps};
+use crate::state::Marketplace;
+
+// Each NFT passes the accounts of `List` as remaining accounts, in the order of `prices`, so every
+// listing goes through the same constraints and account creation as a single `list`
+#[derive(Accounts)]
+pub struct BatchList<'info> {
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+}
+
+impl<'info> BatchList<'info> {
+    pub const ACCOUNTS_PER_NFT: usize = 11;
+
+    pub fn batch_list(
+        &mut self,
+        prices: &[u64],
+        payment_mint: Option<Pubkey>,
+        nfts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(
+            !prices.is_empty() && prices.len() <= MAX_BATCH_LEN,
+            MarketplaceError::BatchTooLarge
+        );
+        require!(
+            nfts.len() == prices.len() * Self::ACCOUNTS_PER_NFT,
+            MarketplaceError::InvalidBatchAccounts
+        );
+
+        for (price, mut accounts) in prices.iter().zip(nfts.chunks_exact(Self::ACCOUNTS_PER_NFT)) {
+            let mut bumps = ListBumps::default();
+            let mut list = List::try_accounts(
+                &crate::ID,
+                &mut accounts,
+                &[],
+                &mut bumps,
+                &mut BTreeSet::new(),
+            )?;
+            require!(
+                list.seller.key() == self.seller.key()
+                    && list.marketplace.key() == self.marketplace.key(),
+                MarketplaceError::InvalidBatchAccounts
+            );
+
+            list.listing(*price, payment_mint, &bumps)?;
+            list.deposit_nft()?;
+            list.exit(&crate::ID)?;
+        }
+
+        Ok(())
+    }
+}
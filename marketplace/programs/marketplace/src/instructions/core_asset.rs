@@ -0,0 +1,294 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::MPL_CORE_PROGRAM_ID;
+use crate::error::*;
+use crate::state::{Listing, Marketplace};
+use crate::utils::{
+    core_royalties_due, mint_trade_rewards, pay_royalties, CoreAsset, CoreTransfer,
+};
+
+// Core assets are plain accounts, the listing takes ownership of the asset itself instead of a vault
+#[derive(Accounts)]
+pub struct ListCore<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    /// CHECK: owner and layout are checked by `CoreAsset::try_from_account`
+    #[account[mut]]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: must be the asset's collection, checked against the asset
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account[
+      init,
+      space = 8+Listing::INIT_SPACE,
+      payer=seller,
+      seeds = [marketplace.key().as_ref(), asset.key().as_ref()],
+      bump
+    ]]
+    pub listing: Account<'info, Listing>,
+    /// CHECK: Metaplex Core
+    #[account[address = MPL_CORE_PROGRAM_ID]]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ListCore<'info> {
+    pub fn listing(&mut self, price: u64, bumps: &ListCoreBumps) -> Result<()> {
+        self.marketplace.check_listing(price, None)?;
+
+        let asset = CoreAsset::try_from_account(&self.asset)?;
+        require_keys_eq!(
+            asset.owner,
+            self.seller.key(),
+            MarketplaceError::NotAssetOwner
+        );
+        check_collection(&asset, self.collection.as_ref())?;
+        require!(
+            self.marketplace.accepts_collection(asset.collection),
+            MarketplaceError::CollectionNotAllowed
+        );
+
+        self.listing.set_inner(Listing {
+            seller: self.seller.key(),
+            mint: self.asset.key(),
+            price,
+            payment_mint: None,
+            delegated: false,
+            bump: bumps.listing,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit_asset(&mut self) -> Result<()> {
+        CoreTransfer {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_deref(),
+            payer: &self.seller,
+            authority: &self.seller,
+            new_owner: &self.listing.to_account_info(),
+            system_program: &self.system_program,
+        }
+        .invoke_signed(&[])
+    }
+}
+
+// Core listings are priced in SOL
+#[derive(Accounts)]
+pub struct PurchaseCore<'info> {
+    #[account[mut]]
+    pub buyer: Signer<'info>,
+    #[account[mut]]
+    pub seller: SystemAccount<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    /// CHECK: the listed asset, checked through `listing.mint`
+    #[account[mut]]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: must be the asset's collection, checked against the asset
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account[
+      mut,
+      has_one = seller,
+      constraint = listing.mint == asset.key(),
+      seeds = [marketplace.key().as_ref(), asset.key().as_ref()],
+      bump = listing.bump,
+      close=seller
+    ]]
+    pub listing: Account<'info, Listing>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      mut,
+      seeds = [b"rewards", marketplace.key().as_ref()],
+      bump = marketplace.reward_bump,
+      mint::token_program = token_program,
+    ]]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = buyer,
+    ]]
+    pub buyer_rewards_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = seller,
+    ]]
+    pub seller_rewards_ata: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Metaplex Core
+    #[account[address = MPL_CORE_PROGRAM_ID]]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> PurchaseCore<'info> {
+    pub fn purchase(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        let asset = CoreAsset::try_from_account(&self.asset)?;
+        check_collection(&asset, self.collection.as_ref())?;
+
+        let price = self.listing.price;
+        let fees = self
+            .marketplace
+            .fee_for(price, self.buyer_rewards_ata.amount)?;
+
+        let royalties = core_royalties_due(
+            &self.marketplace,
+            &self.asset,
+            self.collection.as_deref(),
+            price,
+            !creators.is_empty(),
+        )?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let price_to_be_sent = price
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.seller.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, price_to_be_sent)?;
+
+        if fees > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, fees)?;
+        }
+
+        pay_royalties(
+            &self.buyer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &royalties,
+            creators,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn purchasing_asset(&mut self) -> Result<()> {
+        let asset = CoreAsset::try_from_account(&self.asset)?;
+        check_collection(&asset, self.collection.as_ref())?;
+
+        let mkey = self.marketplace.key();
+        let asset_key = self.asset.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), asset_key.as_ref(), &[self.listing.bump]];
+
+        CoreTransfer {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_deref(),
+            payer: &self.buyer,
+            authority: &self.listing.to_account_info(),
+            new_owner: &self.buyer,
+            system_program: &self.system_program,
+        }
+        .invoke_signed(&[seeds])
+    }
+
+    // Same rewards as `Purchase::reward_trade`, Core listings are always priced in SOL
+    pub fn reward_trade(&mut self) -> Result<()> {
+        mint_trade_rewards(
+            &self.marketplace,
+            &self.reward_mint.to_account_info(),
+            &self.buyer_rewards_ata,
+            &self.seller_rewards_ata,
+            &self.token_program.to_account_info(),
+            None,
+            self.listing.price,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct UnlistCore<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    /// CHECK: the listed asset, checked through `listing.mint`
+    #[account[mut]]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: must be the asset's collection, checked against the asset
+    pub collection: Option<UncheckedAccount<'info>>,
+    #[account[
+      mut,
+      has_one = seller,
+      constraint = listing.mint == asset.key(),
+      seeds = [marketplace.key().as_ref(), asset.key().as_ref()],
+      bump = listing.bump,
+      close=seller
+    ]]
+    pub listing: Account<'info, Listing>,
+    /// CHECK: Metaplex Core
+    #[account[address = MPL_CORE_PROGRAM_ID]]
+    pub core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnlistCore<'info> {
+    pub fn return_asset(&mut self) -> Result<()> {
+        let asset = CoreAsset::try_from_account(&self.asset)?;
+        check_collection(&asset, self.collection.as_ref())?;
+
+        let mkey = self.marketplace.key();
+        let asset_key = self.asset.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), asset_key.as_ref(), &[self.listing.bump]];
+
+        CoreTransfer {
+            core_program: &self.core_program,
+            asset: &self.asset,
+            collection: self.collection.as_deref(),
+            payer: &self.seller,
+            authority: &self.listing.to_account_info(),
+            new_owner: &self.seller,
+            system_program: &self.system_program,
+        }
+        .invoke_signed(&[seeds])
+    }
+}
+
+// Core requires the collection account for assets that belong to one
+fn check_collection(asset: &CoreAsset, collection: Option<&UncheckedAccount>) -> Result<()> {
+    require!(
+        asset.collection == collection.map(|collection| collection.key()),
+        MarketplaceError::InvalidCoreCollection
+    );
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::error::*;
+use crate::state::{Auction, AuctionKind, Marketplace};
+use crate::utils::deposit_nft;
+
+#[derive(Accounts)]
+pub struct CreateAuction<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program=token_program
+    ]]
+    pub seller_mint: Account<'info, Mint>,
+    #[account[
+      mut,
+      associated_token::mint = seller_mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Account<'info, TokenAccount>,
+    #[account[
+      init,
+      payer=seller,
+      associated_token::mint=seller_mint,
+      associated_token::authority=auction,
+    ]]
+    pub vault: Account<'info, TokenAccount>,
+    #[account[
+      init,
+      space = 8 + Auction::INIT_SPACE,
+      payer=seller,
+      seeds = [b"auction", marketplace.key().as_ref(), seller_mint.key().as_ref()],
+      bump
+    ]]
+    pub auction: Account<'info, Auction>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+      constraint = marketplace.accepts(&metadata) @ MarketplaceError::CollectionNotAllowed
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> CreateAuction<'info> {
+    // The auction opens right away and runs until `end_time`
+    pub fn create_auction(
+        &mut self,
+        kind: AuctionKind,
+        end_time: i64,
+        bumps: &CreateAuctionBumps,
+    ) -> Result<()> {
+        let start_time = Clock::get()?.unix_timestamp;
+        Auction::validate(&kind, start_time, end_time)?;
+
+        self.auction.set_inner(Auction {
+            seller: self.seller.key(),
+            mint: self.seller_mint.key(),
+            kind,
+            start_time,
+            end_time,
+            highest_bidder: None,
+            highest_bid: 0,
+            bump: bumps.auction,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit_nft(&mut self) -> Result<()> {
+        deposit_nft(
+            &self.token_program.to_account_info(),
+            &self.seller.to_account_info(),
+            &self.seller_ata.to_account_info(),
+            &self.vault.to_account_info(),
+            &self.seller_mint.to_account_info(),
+        )
+    }
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use anchor_spl::token_interface::{Mint, TokenInterface};
 
+use crate::constants::MAX_FEE_BPS;
 use crate::error::*;
 use crate::state::Marketplace;
 #[derive(Accounts)]
@@ -37,9 +38,10 @@ pub struct Initialize<'info> {
 impl<'info> Initialize<'info> {
     pub fn init(&mut self, name: String, fee: u16, bumps: &InitializeBumps) -> Result<()> {
         require!(
-            !name.is_empty() && name.len() < 4 + 33,
-            MarketplaceError::CustomError1
+            !name.is_empty() && name.len() <= 32,
+            MarketplaceError::InvalidName
         );
+        require!(fee <= MAX_FEE_BPS, MarketplaceError::InvalidFee);
 
         self.marketplace.set_inner(Marketplace {
             admin: self.admin.key(),
@@ -47,7 +49,13 @@ impl<'info> Initialize<'info> {
             bump: bumps.marketplace,
             reward_bump: bumps.reward_mint,
             treasury_bump: bumps.treasury,
-            name: name,
+            enforce_royalties: true,
+            allowed_collections: Vec::new(),
+            payment_mints: Vec::new(),
+            reward_rate_bps: 0,
+            discount_threshold: 0,
+            fee_discount_bps: 0,
+            name,
         });
         Ok(())
     }
@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::transfer;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, Transfer, TransferChecked};
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
+use crate::error::*;
 use crate::state::{Listing, Marketplace};
-use crate::Error::*;
+use crate::utils::deposit_nft;
 #[derive(Accounts)]
-#[instruction(name:String)]
 pub struct List<'info> {
     #[account[mut]]
     pub seller: Signer<'info>,
@@ -40,19 +40,35 @@ pub struct List<'info> {
       bump
     ]]
     pub listing: Account<'info, Listing>,
-    // metadata
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+      constraint = marketplace.accepts(&metadata) @ MarketplaceError::CollectionNotAllowed
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
     // master edition
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
 }
 
 impl<'info> List<'info> {
-    pub fn listing(&mut self, price: u64, bumps: &ListBumps) -> Result<()> {
+    pub fn listing(
+        &mut self,
+        price: u64,
+        payment_mint: Option<Pubkey>,
+        bumps: &ListBumps,
+    ) -> Result<()> {
+        self.marketplace.check_listing(price, payment_mint)?;
+
         self.listing.set_inner(Listing {
             seller: self.seller.key(),
             mint: self.seller_mint.key(),
             price,
+            payment_mint,
+            delegated: false,
             bump: bumps.listing,
         });
 
@@ -60,18 +76,12 @@ impl<'info> List<'info> {
     }
 
     pub fn deposit_nft(&mut self) -> Result<()> {
-        let cpi_ctx = CpiContext::new(
-            self.token_program.to_account_info(),
-            TransferChecked {
-                from: self.seller_ata.to_account_info(),
-                to: self.vault.to_account_info(),
-                authority: self.seller.to_account_info(),
-                mint: self.seller_mint.to_account_info(),
-            },
-        );
-
-        transfer_checked(cpi_ctx, 1, 0);
-
-        Ok(())
+        deposit_nft(
+            &self.token_program.to_account_info(),
+            &self.seller.to_account_info(),
+            &self.seller_ata.to_account_info(),
+            &self.vault.to_account_info(),
+            &self.seller_mint.to_account_info(),
+        )
     }
 }
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    freeze_delegated_account, FreezeDelegatedAccount, MasterEditionAccount, Metadata,
+    MetadataAccount,
+};
+use anchor_spl::token::{approve, Approve, Mint, Token, TokenAccount};
+
+use crate::error::*;
+use crate::state::{Listing, Marketplace};
+
+// Lists without custody, the NFT stays in the seller's wallet, frozen until it sells or is unlisted
+#[derive(Accounts)]
+pub struct ListDelegated<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program=token_program
+    ]]
+    pub seller_mint: Account<'info, Mint>,
+    #[account[
+      mut,
+      associated_token::mint = seller_mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Account<'info, TokenAccount>,
+    #[account[
+      init,
+      space = 8+Listing::INIT_SPACE,
+      payer=seller,
+      seeds = [marketplace.key().as_ref(), seller_mint.key().as_ref()],
+      bump
+    ]]
+    pub listing: Account<'info, Listing>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+      constraint = marketplace.accepts(&metadata) @ MarketplaceError::CollectionNotAllowed
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Box<Account<'info, MasterEditionAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> ListDelegated<'info> {
+    pub fn listing(
+        &mut self,
+        price: u64,
+        payment_mint: Option<Pubkey>,
+        bumps: &ListDelegatedBumps,
+    ) -> Result<()> {
+        self.marketplace.check_listing(price, payment_mint)?;
+
+        self.listing.set_inner(Listing {
+            seller: self.seller.key(),
+            mint: self.seller_mint.key(),
+            price,
+            payment_mint,
+            delegated: true,
+            bump: bumps.listing,
+        });
+
+        Ok(())
+    }
+
+    // Approves the listing as delegate, then has it freeze the token account through the edition
+    pub fn freeze_nft(&mut self) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            Approve {
+                to: self.seller_ata.to_account_info(),
+                delegate: self.listing.to_account_info(),
+                authority: self.seller.to_account_info(),
+            },
+        );
+        approve(cpi_ctx, 1)?;
+
+        let mkey = self.marketplace.key();
+        let mint_key = self.seller_mint.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
+
+        let s = [seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.metadata_program.to_account_info(),
+            FreezeDelegatedAccount {
+                metadata: self.metadata.to_account_info(),
+                delegate: self.listing.to_account_info(),
+                token_account: self.seller_ata.to_account_info(),
+                edition: self.edition.to_account_info(),
+                mint: self.seller_mint.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+            &s,
+        );
+
+        freeze_delegated_account(cpi_ctx)
+    }
+}
@@ -13,3 +13,51 @@ pub use purchase::*;
 pub mod unlist;
 
 pub use unlist::*;
+
+pub mod admin;
+
+pub use admin::*;
+
+pub mod payment_mint;
+
+pub use payment_mint::*;
+
+pub mod offers;
+
+pub use offers::*;
+
+pub mod accept_offer;
+
+pub use accept_offer::*;
+
+pub mod create_auction;
+
+pub use create_auction::*;
+
+pub mod place_bid;
+
+pub use place_bid::*;
+
+pub mod settle_auction;
+
+pub use settle_auction::*;
+
+pub mod list_delegated;
+
+pub use list_delegated::*;
+
+pub mod core_asset;
+
+pub use core_asset::*;
+
+pub mod programmable;
+
+pub use programmable::*;
+
+pub mod batch_list;
+
+pub use batch_list::*;
+
+pub mod sweep_purchase;
+
+pub use sweep_purchase::*;
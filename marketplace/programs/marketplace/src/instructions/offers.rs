@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::Mint;
+
+use crate::error::*;
+use crate::state::{Marketplace, Offer};
+
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account[mut]]
+    pub buyer: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    // the NFT may or may not be listed
+    pub mint: Account<'info, Mint>,
+    #[account[
+      init,
+      space = 8 + Offer::INIT_SPACE,
+      payer = buyer,
+      seeds = [b"offer", marketplace.key().as_ref(), mint.key().as_ref(), buyer.key().as_ref()],
+      bump
+    ]]
+    pub offer: Account<'info, Offer>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeOffer<'info> {
+    pub fn make_offer(
+        &mut self,
+        amount: u64,
+        expires_at: i64,
+        bumps: &MakeOfferBumps,
+    ) -> Result<()> {
+        require!(amount > 0, MarketplaceError::InvalidPrice);
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            MarketplaceError::InvalidExpiry
+        );
+
+        self.offer.set_inner(Offer {
+            buyer: self.buyer.key(),
+            mint: self.mint.key(),
+            amount,
+            expires_at,
+            bump: bumps.offer,
+        });
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.offer.to_account_info(),
+            },
+        );
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// Closing the offer hands the escrowed lamports and the rent back to the buyer
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    #[account[mut]]
+    pub buyer: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mut,
+      has_one = buyer,
+      seeds = [b"offer", marketplace.key().as_ref(), offer.mint.as_ref(), buyer.key().as_ref()],
+      bump = offer.bump,
+      close = buyer
+    ]]
+    pub offer: Account<'info, Offer>,
+}
+
+// Anyone may close an expired offer, the lamports still only go back to the buyer
+#[derive(Accounts)]
+pub struct ReclaimOffer<'info> {
+    pub payer: Signer<'info>,
+    #[account[mut]]
+    pub buyer: SystemAccount<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mut,
+      has_one = buyer,
+      seeds = [b"offer", marketplace.key().as_ref(), offer.mint.as_ref(), buyer.key().as_ref()],
+      bump = offer.bump,
+      close = buyer
+    ]]
+    pub offer: Account<'info, Offer>,
+}
+
+impl<'info> ReclaimOffer<'info> {
+    pub fn reclaim(&mut self) -> Result<()> {
+        require!(
+            self.offer.is_expired(Clock::get()?.unix_timestamp),
+            MarketplaceError::OfferNotExpired
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::*;
+use crate::state::{Marketplace, PaymentMint};
+
+// Allowing a payment mint also opens the treasury account that collects fees in it
+#[derive(Accounts)]
+pub struct AllowPaymentMint<'info> {
+    #[account[mut]]
+    pub admin: Signer<'info>,
+    #[account[
+      mut,
+      has_one = admin,
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      mint::token_program = token_program
+    ]]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    #[account[
+      init_if_needed,
+      payer = admin,
+      associated_token::mint = payment_mint,
+      associated_token::authority = treasury,
+      associated_token::token_program = token_program,
+    ]]
+    pub treasury_payment_ata: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> AllowPaymentMint<'info> {
+    pub fn allow_payment_mint(&mut self, reward_rate_bps: u16) -> Result<()> {
+        let allowed = &mut self.marketplace.payment_mints;
+        let payment_mint = self.payment_mint.key();
+        require!(
+            !allowed.iter().any(|allowed| allowed.mint == payment_mint),
+            MarketplaceError::PaymentMintAlreadyAllowed
+        );
+        require!(
+            allowed.len() < Marketplace::MAX_PAYMENT_MINTS,
+            MarketplaceError::TooManyPaymentMints
+        );
+
+        allowed.push(PaymentMint {
+            mint: payment_mint,
+            reward_rate_bps,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryTokens<'info> {
+    #[account[mut]]
+    pub admin: Signer<'info>,
+    #[account[
+      has_one = admin,
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      mint::token_program = token_program
+    ]]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    #[account[
+      mut,
+      associated_token::mint = payment_mint,
+      associated_token::authority = treasury,
+      associated_token::token_program = token_program,
+    ]]
+    pub treasury_payment_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account[
+      init_if_needed,
+      payer = admin,
+      associated_token::mint = payment_mint,
+      associated_token::authority = admin,
+      associated_token::token_program = token_program,
+    ]]
+    pub admin_payment_ata: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> WithdrawTreasuryTokens<'info> {
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount <= self.treasury_payment_ata.amount,
+            MarketplaceError::InsufficientTreasury
+        );
+
+        let mkey = self.marketplace.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            mkey.as_ref(),
+            &[self.marketplace.treasury_bump],
+        ];
+        let s = [seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: self.treasury_payment_ata.to_account_info(),
+                mint: self.payment_mint.to_account_info(),
+                to: self.admin_payment_ata.to_account_info(),
+                authority: self.treasury.to_account_info(),
+            },
+            &s,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.payment_mint.decimals)
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::error::*;
+use crate::state::{Auction, AuctionKind, Marketplace};
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    #[account[mut]]
+    pub bidder: Signer<'info>,
+    // the bidder being outbid, refunded in the same instruction
+    #[account[mut]]
+    pub previous_bidder: Option<SystemAccount<'info>>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mut,
+      seeds = [b"auction", marketplace.key().as_ref(), auction.mint.as_ref()],
+      bump = auction.bump
+    ]]
+    pub auction: Account<'info, Auction>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceBid<'info> {
+    // On a Dutch auction `amount` is the most the bidder will pay, they are charged the current price
+    pub fn place_bid(&mut self, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.auction.has_ended(now), MarketplaceError::AuctionEnded);
+
+        let bid = match self.auction.kind {
+            AuctionKind::English { extension, .. } => {
+                require!(
+                    amount >= self.auction.min_bid()?,
+                    MarketplaceError::BidTooLow
+                );
+                self.refund_previous_bidder()?;
+
+                // a late bid gives everyone else `extension` seconds to answer
+                if self.auction.end_time - now < extension {
+                    self.auction.end_time = now + extension;
+                }
+                amount
+            }
+            AuctionKind::Dutch { .. } => {
+                let price = self.auction.current_price(now)?;
+                require!(amount >= price, MarketplaceError::BidTooLow);
+
+                // the first bid takes the NFT, the auction can be settled right away
+                self.auction.end_time = now;
+                price
+            }
+        };
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.bidder.to_account_info(),
+                to: self.auction.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, bid)?;
+
+        self.auction.highest_bidder = Some(self.bidder.key());
+        self.auction.highest_bid = bid;
+
+        Ok(())
+    }
+
+    fn refund_previous_bidder(&mut self) -> Result<()> {
+        let Some(highest_bidder) = self.auction.highest_bidder else {
+            return Ok(());
+        };
+        let previous_bidder = self
+            .previous_bidder
+            .as_ref()
+            .filter(|previous| previous.key() == highest_bidder)
+            .ok_or(MarketplaceError::InvalidPreviousBidder)?;
+
+        // the auction is owned by this program, so the escrowed bid moves without a system transfer
+        self.auction.sub_lamports(self.auction.highest_bid)?;
+        previous_bidder.add_lamports(self.auction.highest_bid)?;
+
+        Ok(())
+    }
+}
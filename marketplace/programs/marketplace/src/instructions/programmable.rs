@@ -0,0 +1,483 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{MasterEditionAccount, Metadata, MetadataAccount};
+use anchor_spl::token::{close_account, CloseAccount, Mint, Token, TokenAccount};
+
+use crate::error::*;
+use crate::state::{Listing, Marketplace};
+use crate::utils::{mint_trade_rewards, pay_royalties, royalties_due, ProgrammableTransfer};
+
+// pNFT token accounts are always frozen, so every move goes through Token Metadata's `TransferV1`
+// and its rule set instead of a plain `transfer_checked`
+#[derive(Accounts)]
+pub struct ListProgrammable<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program=token_program
+    ]]
+    pub seller_mint: Box<Account<'info, Mint>>,
+    #[account[
+      mut,
+      associated_token::mint = seller_mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Box<Account<'info, TokenAccount>>,
+    // a vault Token Metadata left frozen behind an earlier listing is reused
+    #[account[
+      init_if_needed,
+      payer=seller,
+      associated_token::mint=seller_mint,
+      associated_token::authority=listing,
+    ]]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account[
+      init,
+      space = 8+Listing::INIT_SPACE,
+      payer=seller,
+      seeds = [marketplace.key().as_ref(), seller_mint.key().as_ref()],
+      bump
+    ]]
+    pub listing: Account<'info, Listing>,
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+      constraint = marketplace.accepts(&metadata) @ MarketplaceError::CollectionNotAllowed
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Box<Account<'info, MasterEditionAccount>>,
+    /// CHECK: token record of `seller_ata`, checked by its seeds
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"token_record", seller_ata.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub seller_token_record: UncheckedAccount<'info>,
+    /// CHECK: token record of `vault`, created by the transfer
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"token_record", vault.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub vault_token_record: UncheckedAccount<'info>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: the instructions sysvar
+    #[account[address = instructions::ID]]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> ListProgrammable<'info> {
+    pub fn listing(&mut self, price: u64, bumps: &ListProgrammableBumps) -> Result<()> {
+        self.marketplace.check_listing(price, None)?;
+
+        self.listing.set_inner(Listing {
+            seller: self.seller.key(),
+            mint: self.seller_mint.key(),
+            price,
+            payment_mint: None,
+            delegated: false,
+            bump: bumps.listing,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit_nft(&mut self) -> Result<()> {
+        ProgrammableTransfer {
+            metadata_program: &self.metadata_program.to_account_info(),
+            token: &self.seller_ata.to_account_info(),
+            token_owner: &self.seller.to_account_info(),
+            destination_token: &self.vault.to_account_info(),
+            destination_owner: &self.listing.to_account_info(),
+            mint: &self.seller_mint.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            edition: &self.edition.to_account_info(),
+            token_record: &self.seller_token_record,
+            destination_token_record: &self.vault_token_record,
+            authority: &self.seller.to_account_info(),
+            payer: &self.seller.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions,
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+        }
+        .invoke_signed(&[])
+    }
+}
+
+// pNFT listings are priced in SOL
+#[derive(Accounts)]
+pub struct PurchaseProgrammable<'info> {
+    #[account[mut]]
+    pub buyer: Signer<'info>,
+    #[account[mut]]
+    pub seller: SystemAccount<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program=token_program
+    ]]
+    pub mint: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = mint,
+      associated_token::authority = buyer
+    ]]
+    pub buyer_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      associated_token::mint=mint,
+      associated_token::authority=listing,
+    ]]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      has_one = mint,
+      has_one = seller,
+      seeds = [marketplace.key().as_ref(), mint.key().as_ref()],
+      bump = listing.bump,
+      close=seller
+    ]]
+    pub listing: Account<'info, Listing>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Box<Account<'info, MasterEditionAccount>>,
+    /// CHECK: token record of `vault`, checked by its seeds
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref(), b"token_record", vault.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub vault_token_record: UncheckedAccount<'info>,
+    /// CHECK: token record of `buyer_ata`, created by the transfer
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref(), b"token_record", buyer_ata.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub buyer_token_record: UncheckedAccount<'info>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    #[account[
+      mut,
+      seeds = [b"rewards", marketplace.key().as_ref()],
+      bump = marketplace.reward_bump,
+      mint::token_program = token_program,
+    ]]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = buyer,
+    ]]
+    pub buyer_rewards_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = seller,
+    ]]
+    pub seller_rewards_ata: Box<Account<'info, TokenAccount>>,
+    /// CHECK: the instructions sysvar
+    #[account[address = instructions::ID]]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> PurchaseProgrammable<'info> {
+    pub fn purchase(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        let price = self.listing.price;
+        let fees = self
+            .marketplace
+            .fee_for(price, self.buyer_rewards_ata.amount)?;
+
+        let royalties = royalties_due(
+            &self.marketplace,
+            &self.metadata,
+            price,
+            !creators.is_empty(),
+        )?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let price_to_be_sent = price
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.seller.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, price_to_be_sent)?;
+
+        if fees > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, fees)?;
+        }
+
+        pay_royalties(
+            &self.buyer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &royalties,
+            creators,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn purchasing_nft(&mut self) -> Result<()> {
+        let mkey = self.marketplace.key();
+        let mint_key = self.mint.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
+
+        ProgrammableTransfer {
+            metadata_program: &self.metadata_program.to_account_info(),
+            token: &self.vault.to_account_info(),
+            token_owner: &self.listing.to_account_info(),
+            destination_token: &self.buyer_ata.to_account_info(),
+            destination_owner: &self.buyer.to_account_info(),
+            mint: &self.mint.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            edition: &self.edition.to_account_info(),
+            token_record: &self.vault_token_record,
+            destination_token_record: &self.buyer_token_record,
+            authority: &self.listing.to_account_info(),
+            payer: &self.buyer.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions,
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+        }
+        .invoke_signed(&[seeds])?;
+
+        close_vault(
+            &self.vault.to_account_info(),
+            &self.seller.to_account_info(),
+            &self.listing.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[seeds],
+        )
+    }
+
+    // Same rewards as `Purchase::reward_trade`, pNFT listings are always priced in SOL
+    pub fn reward_trade(&mut self) -> Result<()> {
+        mint_trade_rewards(
+            &self.marketplace,
+            &self.reward_mint.to_account_info(),
+            &self.buyer_rewards_ata,
+            &self.seller_rewards_ata,
+            &self.token_program.to_account_info(),
+            None,
+            self.listing.price,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct UnlistProgrammable<'info> {
+    #[account[mut]]
+    pub seller: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program=token_program
+    ]]
+    pub seller_mint: Box<Account<'info, Mint>>,
+    #[account[
+      mut,
+      associated_token::mint = seller_mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      associated_token::mint=seller_mint,
+      associated_token::authority=listing,
+    ]]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      has_one = seller,
+      seeds = [marketplace.key().as_ref(), seller_mint.key().as_ref()],
+      bump=listing.bump,
+      close=seller
+    ]]
+    pub listing: Account<'info, Listing>,
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Box<Account<'info, MasterEditionAccount>>,
+    /// CHECK: token record of `vault`, checked by its seeds
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"token_record", vault.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub vault_token_record: UncheckedAccount<'info>,
+    /// CHECK: token record of `seller_ata`, checked by its seeds
+    #[account[
+      mut,
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"token_record", seller_ata.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub seller_token_record: UncheckedAccount<'info>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by Token Metadata against the NFT's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: the instructions sysvar
+    #[account[address = instructions::ID]]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> UnlistProgrammable<'info> {
+    pub fn return_nft(&mut self) -> Result<()> {
+        let mkey = self.marketplace.key();
+        let mint_key = self.seller_mint.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
+
+        ProgrammableTransfer {
+            metadata_program: &self.metadata_program.to_account_info(),
+            token: &self.vault.to_account_info(),
+            token_owner: &self.listing.to_account_info(),
+            destination_token: &self.seller_ata.to_account_info(),
+            destination_owner: &self.seller.to_account_info(),
+            mint: &self.seller_mint.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            edition: &self.edition.to_account_info(),
+            token_record: &self.vault_token_record,
+            destination_token_record: &self.seller_token_record,
+            authority: &self.listing.to_account_info(),
+            payer: &self.seller.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions,
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+        }
+        .invoke_signed(&[seeds])?;
+
+        close_vault(
+            &self.vault.to_account_info(),
+            &self.seller.to_account_info(),
+            &self.listing.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[seeds],
+        )
+    }
+}
+
+// Token Metadata thaws the vault to move the pNFT out and leaves it open, its rent goes back to
+// the seller who paid for it. A vault that is still frozen stays for the next listing to reuse
+fn close_vault<'info>(
+    vault: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    listing: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if vault.owner != token_program.key || vault.data_is_empty() {
+        return Ok(());
+    }
+    let frozen = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.is_frozen();
+    if frozen {
+        return Ok(());
+    }
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: vault.clone(),
+            destination: seller.clone(),
+            authority: listing.clone(),
+        },
+        signer_seeds,
+    );
+
+    close_account(cpi_ctx)
+}
@@ -1,15 +1,24 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::system_instruction::transfer;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, Transfer, TransferChecked};
+use anchor_spl::metadata::{MasterEditionAccount, Metadata, MetadataAccount};
+use anchor_spl::token::{
+    close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+};
+use anchor_spl::token_interface::{self, TokenInterface};
 
 use crate::error::*;
 use crate::state::{Listing, Marketplace};
+use crate::utils::{
+    mint_trade_rewards, pay_royalties, pay_token_royalties, royalties_due, thaw_nft,
+};
+
 #[derive(Accounts)]
-#[instruction(name:String)]
 pub struct Purchase<'info> {
     #[account[mut]]
     pub buyer: Signer<'info>,
+    #[account[mut]]
+    pub seller: SystemAccount<'info>,
     #[account[
       mint::token_program=token_program,
     ]]
@@ -21,76 +30,278 @@ pub struct Purchase<'info> {
     pub marketplace: Account<'info, Marketplace>,
     #[account[
       mut,
-      associated_token::mint = listing.mint,
+      associated_token::mint = mint,
       associated_token::authority = buyer
     ]]
     pub buyer_ata: Account<'info, TokenAccount>,
+    // custodial listings hold the NFT in the vault, delegated ones in the seller's account
     #[account[
       mut,
-      associated_token::mint=listing.mint,
+      associated_token::mint=mint,
       associated_token::authority=listing,
-      close=buyer
     ]]
-    pub vault_ata: Account<'info, TokenAccount>,
+    pub vault_ata: Option<Box<Account<'info, TokenAccount>>>,
     #[account[
       mut,
-      seeds = [marketplace.key().as_ref(), listing.mint.key().as_ref()],
+      associated_token::mint = mint,
+      associated_token::authority = seller
+    ]]
+    pub seller_ata: Option<Box<Account<'info, TokenAccount>>>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
       bump,
-      close=buyer
+    ]]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+    #[account[
+      mut,
+      has_one = mint,
+      has_one = seller,
+      seeds = [marketplace.key().as_ref(), mint.key().as_ref()],
+      bump = listing.bump,
+      close=seller
     ]]
     pub listing: Account<'info, Listing>,
     #[account[
-       seeds=[b"treasury", marketplace.key().as_ref()],
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
       bump=marketplace.treasury_bump
     ]]
     pub treasury: SystemAccount<'info>,
-    // metadata
-    // master edition
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account[
+      mut,
+      seeds = [b"rewards", marketplace.key().as_ref()],
+      bump = marketplace.reward_bump,
+      mint::token_program = token_program,
+    ]]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = buyer,
+    ]]
+    pub buyer_rewards_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = seller,
+    ]]
+    pub seller_rewards_ata: Box<Account<'info, TokenAccount>>,
+    // Only needed when the listing is priced in a payment mint
+    #[account[
+      mint::token_program = payment_token_program,
+    ]]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account[
+      mut,
+      associated_token::mint = payment_mint,
+      associated_token::authority = buyer,
+      associated_token::token_program = payment_token_program,
+    ]]
+    pub buyer_payment_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account[
+      mut,
+      associated_token::mint = payment_mint,
+      associated_token::authority = seller,
+      associated_token::token_program = payment_token_program,
+    ]]
+    pub seller_payment_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    #[account[
+      mut,
+      associated_token::mint = payment_mint,
+      associated_token::authority = treasury,
+      associated_token::token_program = payment_token_program,
+    ]]
+    pub treasury_payment_ata: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub payment_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
 }
 
 impl<'info> Purchase<'info> {
-    pub fn purchase(&mut self) -> Result<()> {
-        require!(
-            self.mint.key() == self.listing.mint.key(),
-            MarketplaceError::CustomError1
-        );
-        // transfer of SOL
+    pub fn purchase(&mut self, creators: &'info [AccountInfo<'info>]) -> Result<()> {
         let price = self.listing.price;
-        // let fees = price * (self.marketplace.fees as u64 / 10000);
-        let fees = price
-            .checked_mul((self.marketplace.fees as u64))
-            .unwrap()
-            .checked_div(10000_u64)
-            .unwrap();
-
-        let price_to_be_sent = price.checked_sub(fees).unwrap();
-
-        transfer(
-            &self.buyer.key(),
-            &self.listing.seller.key(),
-            price_to_be_sent,
+        let fees = self
+            .marketplace
+            .fee_for(price, self.buyer_rewards_ata.amount)?;
+
+        let royalties = royalties_due(
+            &self.marketplace,
+            &self.metadata,
+            price,
+            !creators.is_empty(),
+        )?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let price_to_be_sent = price
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        match self.listing.payment_mint {
+            Some(payment_mint) => {
+                self.pay_in_tokens(payment_mint, price_to_be_sent, fees, &royalties, creators)
+            }
+            None => self.pay_in_sol(price_to_be_sent, fees, &royalties, creators),
+        }
+    }
+
+    fn pay_in_sol(
+        &self,
+        price_to_be_sent: u64,
+        fees: u64,
+        royalties: &[(Pubkey, u64)],
+        creators: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.seller.to_account_info(),
+            },
         );
+        transfer(cpi_ctx, price_to_be_sent)?;
 
-        transfer(&self.buyer.key(), &self.treasury.key(), fees);
+        if fees > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, fees)?;
+        }
+
+        pay_royalties(
+            &self.buyer.to_account_info(),
+            &self.system_program.to_account_info(),
+            royalties,
+            creators,
+        )?;
+
+        Ok(())
+    }
+
+    // Proceeds go to the seller's account for the mint and fees to the treasury's
+    fn pay_in_tokens(
+        &self,
+        payment_mint: Pubkey,
+        price_to_be_sent: u64,
+        fees: u64,
+        royalties: &[(Pubkey, u64)],
+        creator_atas: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let (
+            Some(mint),
+            Some(buyer_ata),
+            Some(seller_ata),
+            Some(treasury_ata),
+            Some(token_program),
+        ) = (
+            &self.payment_mint,
+            &self.buyer_payment_ata,
+            &self.seller_payment_ata,
+            &self.treasury_payment_ata,
+            &self.payment_token_program,
+        )
+        else {
+            return err!(MarketplaceError::MissingPaymentAccounts);
+        };
+        require_keys_eq!(
+            mint.key(),
+            payment_mint,
+            MarketplaceError::PaymentMintMismatch
+        );
+
+        for (to, amount) in [
+            (seller_ata.to_account_info(), price_to_be_sent),
+            (treasury_ata.to_account_info(), fees),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_ctx = CpiContext::new(
+                token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: buyer_ata.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to,
+                    authority: self.buyer.to_account_info(),
+                },
+            );
+            token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+        }
+
+        pay_token_royalties(
+            &self.buyer.to_account_info(),
+            buyer_ata,
+            mint,
+            token_program,
+            royalties,
+            creator_atas,
+        )?;
 
         Ok(())
     }
 
     pub fn purchasing_nft(&mut self) -> Result<()> {
         let mkey = self.marketplace.key();
-        let lkey = self.listing.key();
+        let mint_key = self.mint.key();
 
-        let seeds: &[&[u8]] = &[mkey.as_ref(), lkey.as_ref(), &[self.listing.bump]];
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
 
         let s = [seeds];
 
+        if self.listing.delegated {
+            let (Some(seller_ata), Some(edition)) = (&self.seller_ata, &self.edition) else {
+                return err!(MarketplaceError::MissingListingAccounts);
+            };
+
+            thaw_nft(
+                &self.metadata_program.to_account_info(),
+                &self.listing.to_account_info(),
+                &seller_ata.to_account_info(),
+                &edition.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &s,
+            )?;
+
+            // the listing is still the delegate, so it can move the NFT out of the seller's account
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: seller_ata.to_account_info(),
+                    to: self.buyer_ata.to_account_info(),
+                    authority: self.listing.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+                &s,
+            );
+
+            return transfer_checked(cpi_ctx, 1, 0);
+        }
+
+        let vault_ata = self
+            .vault_ata
+            .as_ref()
+            .ok_or(MarketplaceError::MissingListingAccounts)?;
+
         let cpi_ctx = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             TransferChecked {
-                from: self.vault_ata.to_account_info(),
+                from: vault_ata.to_account_info(),
                 to: self.buyer_ata.to_account_info(),
                 authority: self.listing.to_account_info(),
                 mint: self.mint.to_account_info(),
@@ -98,8 +309,33 @@ impl<'info> Purchase<'info> {
             &s,
         );
 
-        transfer_checked(cpi_ctx, 1, 0);
+        transfer_checked(cpi_ctx, 1, 0)?;
 
-        Ok(())
+        // the seller paid for the vault, so its rent goes back to them
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault_ata.to_account_info(),
+                destination: self.seller.to_account_info(),
+                authority: self.listing.to_account_info(),
+            },
+            &s,
+        );
+
+        close_account(cpi_ctx)
+    }
+
+    // The buyer and the seller each earn reward tokens in proportion to the price, at the rate of
+    // the mint the listing is priced in
+    pub fn reward_trade(&mut self) -> Result<()> {
+        mint_trade_rewards(
+            &self.marketplace,
+            &self.reward_mint.to_account_info(),
+            &self.buyer_rewards_ata,
+            &self.seller_rewards_ata,
+            &self.token_program.to_account_info(),
+            self.listing.payment_mint,
+            self.listing.price,
+        )
     }
 }
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{
+    close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+};
+
+use crate::error::*;
+use crate::state::{Auction, Marketplace};
+use crate::utils::{basis_points, pay_royalties_from_escrow, royalties_due};
+
+// Anyone may settle once the auction has ended, paying for the recipient's token account if needed
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    #[account[mut]]
+    pub payer: Signer<'info>,
+    #[account[mut]]
+    pub seller: SystemAccount<'info>,
+    #[account[
+      constraint = recipient.key() == auction.highest_bidder.unwrap_or(auction.seller)
+        @ MarketplaceError::InvalidAuctionRecipient
+    ]]
+    pub recipient: SystemAccount<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mint::token_program = token_program,
+    ]]
+    pub mint: Account<'info, Mint>,
+    #[account[
+      mut,
+      has_one = seller,
+      has_one = mint,
+      seeds = [b"auction", marketplace.key().as_ref(), mint.key().as_ref()],
+      bump = auction.bump,
+      close = seller
+    ]]
+    pub auction: Account<'info, Auction>,
+    #[account[
+      mut,
+      associated_token::mint = mint,
+      associated_token::authority = auction,
+    ]]
+    pub vault: Account<'info, TokenAccount>,
+    #[account[
+      init_if_needed,
+      payer = payer,
+      associated_token::mint = mint,
+      associated_token::authority = recipient,
+    ]]
+    pub recipient_ata: Box<Account<'info, TokenAccount>>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), mint.key().as_ref()],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> SettleAuction<'info> {
+    // Splits the winning bid between the seller, the treasury and the verified creators
+    pub fn settle(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.auction.has_ended(Clock::get()?.unix_timestamp),
+            MarketplaceError::AuctionNotEnded
+        );
+        if self.auction.highest_bidder.is_none() {
+            return Ok(());
+        }
+
+        let bid = self.auction.highest_bid;
+        let fees = basis_points(bid, self.marketplace.fees)?;
+        let royalties =
+            royalties_due(&self.marketplace, &self.metadata, bid, !creators.is_empty())?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let bid_to_be_sent = bid
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        self.auction.sub_lamports(bid_to_be_sent)?;
+        self.seller.add_lamports(bid_to_be_sent)?;
+        self.auction.sub_lamports(fees)?;
+        self.treasury.add_lamports(fees)?;
+
+        pay_royalties_from_escrow(&self.auction.to_account_info(), &royalties, creators)?;
+
+        Ok(())
+    }
+
+    // The NFT goes to the winner, or back to the seller when nobody bid
+    pub fn release_nft(&mut self) -> Result<()> {
+        let mkey = self.marketplace.key();
+        let mint_key = self.mint.key();
+
+        let seeds: &[&[u8]] = &[
+            b"auction",
+            mkey.as_ref(),
+            mint_key.as_ref(),
+            &[self.auction.bump],
+        ];
+
+        let s = [seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: self.vault.to_account_info(),
+                to: self.recipient_ata.to_account_info(),
+                authority: self.auction.to_account_info(),
+                mint: self.mint.to_account_info(),
+            },
+            &s,
+        );
+
+        transfer_checked(cpi_ctx, 1, 0)?;
+
+        // the seller paid for the vault, so its rent goes back to them
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                destination: self.seller.to_account_info(),
+                authority: self.auction.to_account_info(),
+            },
+            &s,
+        );
+
+        close_account(cpi_ctx)
+    }
+}
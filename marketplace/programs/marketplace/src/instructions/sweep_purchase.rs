@@ -0,0 +1,306 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::{
+    create_idempotent, get_associated_token_address, AssociatedToken, Create,
+};
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{
+    close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+};
+
+use crate::constants::MAX_BATCH_LEN;
+use crate::error::*;
+use crate::state::{Listing, Marketplace};
+use crate::utils::{mint_rewards, pay_royalties, royalties_due};
+
+// Each listing passes `listing, seller, mint, vault, buyer_ata, metadata, seller_rewards_ata` as
+// remaining accounts, followed by its verified creators when royalties are enforced or the buyer
+// pays them anyway. Listings already bought or unlisted are skipped, so a sweep buys up to `count`
+// of them
+//
+// Only custodial listings priced in SOL can be swept. Every listing bought earns the same reward
+// tokens as `purchase`, the buyer's are minted once after the whole sweep
+#[derive(Accounts)]
+pub struct SweepPurchase<'info> {
+    #[account[mut]]
+    pub buyer: Signer<'info>,
+    #[account[
+      seeds = [b"marketplace", marketplace.name.as_bytes()],
+      bump = marketplace.bump
+    ]]
+    pub marketplace: Account<'info, Marketplace>,
+    #[account[
+      mut,
+      seeds=[b"treasury", marketplace.key().as_ref()],
+      bump=marketplace.treasury_bump
+    ]]
+    pub treasury: SystemAccount<'info>,
+    #[account[
+      mut,
+      seeds = [b"rewards", marketplace.key().as_ref()],
+      bump = marketplace.reward_bump,
+      mint::token_program = token_program,
+    ]]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account[
+      init_if_needed,
+      payer = buyer,
+      associated_token::mint = reward_mint,
+      associated_token::authority = buyer,
+    ]]
+    pub buyer_rewards_ata: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> SweepPurchase<'info> {
+    pub const ACCOUNTS_PER_LISTING: usize = 7;
+
+    // Reverts if the listings bought cost more than `max_total` lamports overall
+    pub fn sweep(
+        &mut self,
+        count: u8,
+        max_total: u64,
+        remaining: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(
+            count > 0 && count as usize <= MAX_BATCH_LEN,
+            MarketplaceError::BatchTooLarge
+        );
+
+        let mut total: u64 = 0;
+        let mut buyer_rewards: u64 = 0;
+        let mut rest = remaining;
+        for _ in 0..count {
+            require!(
+                rest.len() >= Self::ACCOUNTS_PER_LISTING,
+                MarketplaceError::InvalidBatchAccounts
+            );
+            let (accounts, after) = rest.split_at(Self::ACCOUNTS_PER_LISTING);
+
+            let (price, rewards, creators_used) = self.purchase_one(accounts, after)?;
+            total = total.checked_add(price).ok_or(MarketplaceError::Overflow)?;
+            require!(total <= max_total, MarketplaceError::SweepPriceExceeded);
+            buyer_rewards = buyer_rewards
+                .checked_add(rewards)
+                .ok_or(MarketplaceError::Overflow)?;
+
+            rest = &after[creators_used..];
+        }
+        require!(rest.is_empty(), MarketplaceError::InvalidBatchAccounts);
+
+        mint_rewards(
+            &self.marketplace,
+            &self.reward_mint.to_account_info(),
+            &self.buyer_rewards_ata.to_account_info(),
+            &self.token_program.to_account_info(),
+            buyer_rewards,
+        )
+    }
+
+    // Returns the price paid and the buyer's reward tokens, both zero for a skipped listing, and
+    // how many creator accounts were taken from `creators`. The seller's rewards are minted here
+    fn purchase_one(
+        &self,
+        accounts: &'info [AccountInfo<'info>],
+        creators: &'info [AccountInfo<'info>],
+    ) -> Result<(u64, u64, usize)> {
+        let [listing_info, seller, mint, vault, buyer_ata, metadata, seller_rewards_ata] = accounts
+        else {
+            return err!(MarketplaceError::InvalidBatchAccounts);
+        };
+
+        let mkey = self.marketplace.key();
+        let mint_key = mint.key();
+        let (metadata_key, _) = Pubkey::find_program_address(
+            &[
+                b"metadata",
+                self.metadata_program.key().as_ref(),
+                mint_key.as_ref(),
+            ],
+            &self.metadata_program.key(),
+        );
+        require_keys_eq!(
+            metadata.key(),
+            metadata_key,
+            MarketplaceError::InvalidBatchAccounts
+        );
+        let metadata = Account::<MetadataAccount>::try_from(metadata)?;
+        let creators = &creators[..creators_passed(&metadata, creators)];
+
+        if listing_info.owner != &crate::ID || listing_info.data_is_empty() {
+            return Ok((0, 0, creators.len()));
+        }
+
+        let listing = Account::<Listing>::try_from(listing_info)?;
+        let listing_key = Pubkey::create_program_address(
+            &[mkey.as_ref(), mint_key.as_ref(), &[listing.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| MarketplaceError::InvalidBatchAccounts)?;
+        require!(
+            listing_info.key() == listing_key
+                && listing.mint == mint_key
+                && listing.seller == seller.key(),
+            MarketplaceError::InvalidBatchAccounts
+        );
+        require!(
+            listing.payment_mint.is_none() && !listing.delegated,
+            MarketplaceError::UnsupportedListing
+        );
+
+        Account::<Mint>::try_from(mint)?;
+        require!(
+            vault.key() == get_associated_token_address(&listing_key, &mint_key)
+                && buyer_ata.key() == get_associated_token_address(&self.buyer.key(), &mint_key),
+            MarketplaceError::InvalidBatchAccounts
+        );
+
+        let price = listing.price;
+        // the buyer's rewards are minted after the sweep, so every listing gets the same discount
+        let fees = self
+            .marketplace
+            .fee_for(price, self.buyer_rewards_ata.amount)?;
+        let royalties = royalties_due(&self.marketplace, &metadata, price, !creators.is_empty())?;
+        let royalty_total: u64 = royalties.iter().map(|(_, amount)| amount).sum();
+
+        let price_to_be_sent = price
+            .checked_sub(fees)
+            .and_then(|rest| rest.checked_sub(royalty_total))
+            .ok_or(MarketplaceError::FeesExceedPrice)?;
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: seller.clone(),
+            },
+        );
+        transfer(cpi_ctx, price_to_be_sent)?;
+
+        if fees > 0 {
+            let cpi_ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            );
+            transfer(cpi_ctx, fees)?;
+        }
+
+        pay_royalties(
+            &self.buyer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &royalties,
+            creators,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            Create {
+                payer: self.buyer.to_account_info(),
+                associated_token: buyer_ata.clone(),
+                authority: self.buyer.to_account_info(),
+                mint: mint.clone(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        );
+        create_idempotent(cpi_ctx)?;
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[listing.bump]];
+        let s = [seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: vault.clone(),
+                to: buyer_ata.clone(),
+                authority: listing_info.clone(),
+                mint: mint.clone(),
+            },
+            &s,
+        );
+        transfer_checked(cpi_ctx, 1, 0)?;
+
+        // the seller paid for the vault and the listing, so their rent goes back to them
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault.clone(),
+                destination: seller.clone(),
+                authority: listing_info.clone(),
+            },
+            &s,
+        );
+        close_account(cpi_ctx)?;
+        listing.close(seller.clone())?;
+
+        let rewards = self.reward_seller(seller, seller_rewards_ata, price, fees)?;
+
+        Ok((price, rewards, creators.len()))
+    }
+
+    // Mints the seller their reward tokens and returns the buyer's share, which is the same
+    fn reward_seller(
+        &self,
+        seller: &'info AccountInfo<'info>,
+        seller_rewards_ata: &'info AccountInfo<'info>,
+        price: u64,
+        fees: u64,
+    ) -> Result<u64> {
+        // buying your own listing earns nothing
+        if seller.key() == self.buyer.key() {
+            return Ok(0);
+        }
+
+        let reward_mint = self.reward_mint.key();
+        require_keys_eq!(
+            seller_rewards_ata.key(),
+            get_associated_token_address(&seller.key(), &reward_mint),
+            MarketplaceError::InvalidBatchAccounts
+        );
+
+        let cpi_ctx = CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            Create {
+                payer: self.buyer.to_account_info(),
+                associated_token: seller_rewards_ata.clone(),
+                authority: seller.clone(),
+                mint: self.reward_mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        );
+        create_idempotent(cpi_ctx)?;
+
+        let rewards = self.marketplace.rewards_for(None, price, fees)?;
+        mint_rewards(
+            &self.marketplace,
+            &self.reward_mint.to_account_info(),
+            seller_rewards_ata,
+            &self.token_program.to_account_info(),
+            rewards,
+        )?;
+
+        Ok(rewards)
+    }
+}
+
+// A listing's creator accounts are the ones right after it that are verified creators of its NFT,
+// the next listing starts with its listing PDA instead
+fn creators_passed(metadata: &MetadataAccount, creators: &[AccountInfo]) -> usize {
+    creators
+        .iter()
+        .take_while(|account| {
+            metadata
+                .creators
+                .iter()
+                .flatten()
+                .any(|creator| creator.verified && creator.address == account.key())
+        })
+        .count()
+}
@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::transfer;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, Transfer, TransferChecked};
+use anchor_spl::metadata::{MasterEditionAccount, Metadata};
+use anchor_spl::token::{
+    close_account, revoke, transfer_checked, CloseAccount, Mint, Revoke, Token, TokenAccount,
+    TransferChecked,
+};
 
 use crate::error::*;
 use crate::state::{Listing, Marketplace};
+use crate::utils::thaw_nft;
 #[derive(Accounts)]
-#[instruction(name:String)]
 pub struct Unlist<'info> {
     #[account[mut]]
     pub seller: Signer<'info>,
@@ -25,43 +28,95 @@ pub struct Unlist<'info> {
       associated_token::authority = seller
     ]]
     pub seller_ata: Account<'info, TokenAccount>,
+    // only custodial listings have a vault
     #[account[
       mut,
-      close=seller,
       associated_token::mint=seller_mint,
       associated_token::authority=listing,
     ]]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: Option<Account<'info, TokenAccount>>,
     #[account[
+      mut,
+      has_one = seller,
       seeds = [marketplace.key().as_ref(), seller_mint.key().as_ref()],
-      bump=listing.bump
+      bump=listing.bump,
+      close=seller
     ]]
     pub listing: Account<'info, Listing>,
-    // metadata
-    // master edition
+    // only needed to thaw delegated listings
+    #[account[
+      seeds=[b"metadata", metadata_program.key().as_ref(), seller_mint.key().as_ref(), b"edition"],
+      seeds::program = metadata_program.key(),
+      bump,
+    ]]
+    pub edition: Option<Box<Account<'info, MasterEditionAccount>>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
 }
 
 impl<'info> Unlist<'info> {
     pub fn unlisting(&mut self) -> Result<()> {
-        self.return_nft();
-        Ok(())
+        if self.listing.delegated {
+            self.unfreeze_nft()
+        } else {
+            self.return_nft()
+        }
+    }
+
+    // Thaws the seller's token account and revokes the listing's delegation
+    pub fn unfreeze_nft(&mut self) -> Result<()> {
+        let edition = self
+            .edition
+            .as_ref()
+            .ok_or(MarketplaceError::MissingListingAccounts)?;
+
+        let mkey = self.marketplace.key();
+        let mint_key = self.seller_mint.key();
+
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
+
+        let s = [seeds];
+
+        thaw_nft(
+            &self.metadata_program.to_account_info(),
+            &self.listing.to_account_info(),
+            &self.seller_ata.to_account_info(),
+            &edition.to_account_info(),
+            &self.seller_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &s,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            Revoke {
+                source: self.seller_ata.to_account_info(),
+                authority: self.seller.to_account_info(),
+            },
+        );
+
+        revoke(cpi_ctx)
     }
 
     pub fn return_nft(&mut self) -> Result<()> {
+        let vault = self
+            .vault
+            .as_ref()
+            .ok_or(MarketplaceError::MissingListingAccounts)?;
+
         let mkey = self.marketplace.key();
-        let lkey = self.listing.key();
+        let mint_key = self.seller_mint.key();
 
-        let seeds: &[&[u8]] = &[mkey.as_ref(), lkey.as_ref(), &[self.listing.bump]];
+        let seeds: &[&[u8]] = &[mkey.as_ref(), mint_key.as_ref(), &[self.listing.bump]];
 
         let s = [seeds];
 
         let cpi_ctx = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             TransferChecked {
-                from: self.vault.to_account_info(),
+                from: vault.to_account_info(),
                 to: self.seller_ata.to_account_info(),
                 authority: self.listing.to_account_info(),
                 mint: self.seller_mint.to_account_info(),
@@ -69,8 +124,18 @@ impl<'info> Unlist<'info> {
             &s,
         );
 
-        transfer_checked(cpi_ctx, 1, 0);
+        transfer_checked(cpi_ctx, 1, 0)?;
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault.to_account_info(),
+                destination: self.seller.to_account_info(),
+                authority: self.listing.to_account_info(),
+            },
+            &s,
+        );
 
-        Ok(())
+        close_account(cpi_ctx)
     }
 }
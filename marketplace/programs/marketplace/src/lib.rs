@@ -2,6 +2,7 @@ pub mod constants;
 pub mod error;
 pub mod instructions;
 pub mod state;
+pub mod utils;
 
 use anchor_lang::prelude::*;
 
@@ -14,20 +15,174 @@ declare_id!("GL7yCFFDvAQxqrN2U8KEPa8duuFiZEQFxi3S8dosfi8j");
 #[program]
 pub mod marketplace {
     use super::*;
-    pub fn listing_nft(ctx: Context<List>, price: u64) -> Result<()> {
-        ctx.accounts.listing(price, &ctx.bumps);
-        ctx.accounts.deposit_nft();
-        Ok(())
+    pub fn initialize(ctx: Context<Initialize>, name: String, fee: u16) -> Result<()> {
+        ctx.accounts.init(name, fee, &ctx.bumps)
+    }
+
+    // Without a payment mint the price is in lamports
+    pub fn listing_nft(ctx: Context<List>, price: u64, payment_mint: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.listing(price, payment_mint, &ctx.bumps)?;
+        ctx.accounts.deposit_nft()
+    }
+
+    // Keeps the NFT in the seller's wallet, frozen with the listing as delegate
+    pub fn list_delegated_nft(
+        ctx: Context<ListDelegated>,
+        price: u64,
+        payment_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.listing(price, payment_mint, &ctx.bumps)?;
+        ctx.accounts.freeze_nft()
+    }
+
+    // Verified creators of the NFT are passed as remaining accounts, in metadata order, or their
+    // payment mint token accounts when the listing is priced in a token
+    pub fn purchase_nft<'info>(ctx: Context<'_, '_, 'info, 'info, Purchase<'info>>) -> Result<()> {
+        ctx.accounts.purchase(ctx.remaining_accounts)?;
+        ctx.accounts.purchasing_nft()?;
+        ctx.accounts.reward_trade()
     }
 
-    pub fn purchase_nft(ctx: Context<Purchase>) -> Result<()> {
-        ctx.accounts.purchase();
-        ctx.accounts.purchasing_nft();
+    pub fn make_offer(ctx: Context<MakeOffer>, amount: u64, expires_at: i64) -> Result<()> {
+        ctx.accounts.make_offer(amount, expires_at, &ctx.bumps)
+    }
+
+    // Verified creators are passed as remaining accounts, as for `purchase_nft`
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptOffer<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.accept_offer(ctx.remaining_accounts)?;
+        ctx.accounts.transfer_nft()
+    }
+
+    pub fn cancel_offer(_ctx: Context<CancelOffer>) -> Result<()> {
         Ok(())
     }
 
+    pub fn reclaim_offer(ctx: Context<ReclaimOffer>) -> Result<()> {
+        ctx.accounts.reclaim()
+    }
+
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        kind: AuctionKind,
+        end_time: i64,
+    ) -> Result<()> {
+        ctx.accounts.create_auction(kind, end_time, &ctx.bumps)?;
+        ctx.accounts.deposit_nft()
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        ctx.accounts.place_bid(amount)
+    }
+
+    // Verified creators are passed as remaining accounts, as for `purchase_nft`
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.settle(ctx.remaining_accounts)?;
+        ctx.accounts.release_nft()
+    }
+
+    pub fn batch_list<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchList<'info>>,
+        prices: Vec<u64>,
+        payment_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .batch_list(&prices, payment_mint, ctx.remaining_accounts)
+    }
+
+    pub fn sweep_purchase<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepPurchase<'info>>,
+        count: u8,
+        max_total: u64,
+    ) -> Result<()> {
+        ctx.accounts.sweep(count, max_total, ctx.remaining_accounts)
+    }
+
     pub fn unlist_nft(ctx: Context<Unlist>) -> Result<()> {
-        ctx.accounts.unlisting();
-        Ok(())
+        ctx.accounts.unlisting()
+    }
+
+    pub fn list_core_asset(ctx: Context<ListCore>, price: u64) -> Result<()> {
+        ctx.accounts.listing(price, &ctx.bumps)?;
+        ctx.accounts.deposit_asset()
+    }
+
+    // Creators from the asset's Royalties plugin are passed as remaining accounts, in plugin order
+    pub fn purchase_core_asset<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseCore<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.purchase(ctx.remaining_accounts)?;
+        ctx.accounts.purchasing_asset()?;
+        ctx.accounts.reward_trade()
+    }
+
+    pub fn unlist_core_asset(ctx: Context<UnlistCore>) -> Result<()> {
+        ctx.accounts.return_asset()
+    }
+
+    pub fn list_programmable_nft(ctx: Context<ListProgrammable>, price: u64) -> Result<()> {
+        ctx.accounts.listing(price, &ctx.bumps)?;
+        ctx.accounts.deposit_nft()
+    }
+
+    // Verified creators are passed as remaining accounts, as for `purchase_nft`
+    pub fn purchase_programmable_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseProgrammable<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.purchase(ctx.remaining_accounts)?;
+        ctx.accounts.purchasing_nft()?;
+        ctx.accounts.reward_trade()
+    }
+
+    pub fn unlist_programmable_nft(ctx: Context<UnlistProgrammable>) -> Result<()> {
+        ctx.accounts.return_nft()
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn allow_payment_mint(ctx: Context<AllowPaymentMint>, reward_rate_bps: u16) -> Result<()> {
+        ctx.accounts.allow_payment_mint(reward_rate_bps)
+    }
+
+    pub fn disallow_payment_mint(ctx: Context<AdminUpdate>, payment_mint: Pubkey) -> Result<()> {
+        ctx.accounts.disallow_payment_mint(payment_mint)
+    }
+
+    pub fn update_fee(ctx: Context<AdminUpdate>, fee: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee)
+    }
+
+    pub fn set_royalty_enforcement(ctx: Context<AdminUpdate>, enforce: bool) -> Result<()> {
+        ctx.accounts.set_royalty_enforcement(enforce)
+    }
+
+    pub fn set_rewards(
+        ctx: Context<AdminUpdate>,
+        reward_rate_bps: u16,
+        discount_threshold: u64,
+        fee_discount_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_rewards(reward_rate_bps, discount_threshold, fee_discount_bps)
+    }
+
+    pub fn allow_collection(ctx: Context<AdminUpdate>, collection: Pubkey) -> Result<()> {
+        ctx.accounts.allow_collection(collection)
+    }
+
+    pub fn disallow_collection(ctx: Context<AdminUpdate>, collection: Pubkey) -> Result<()> {
+        ctx.accounts.disallow_collection(collection)
     }
 }
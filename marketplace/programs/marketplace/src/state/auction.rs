@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AuctionKind {
+    // ascending bids, a bid close to the end pushes the end back by `extension` seconds
+    English {
+        reserve_price: u64,
+        min_increment: u64,
+        extension: i64,
+    },
+    // the price falls linearly from `start_price` to `end_price`, the first bid wins
+    Dutch {
+        start_price: u64,
+        end_price: u64,
+    },
+}
+
+// The NFT sits in the auction's vault and the leading bid in the auction account itself
+#[account]
+#[derive(InitSpace)]
+pub struct Auction {
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub kind: AuctionKind,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub highest_bidder: Option<Pubkey>,
+    pub highest_bid: u64,
+    pub bump: u8,
+}
+
+impl Auction {
+    pub fn validate(kind: &AuctionKind, start_time: i64, end_time: i64) -> Result<()> {
+        require!(end_time > start_time, MarketplaceError::InvalidAuction);
+
+        match *kind {
+            // with a zero increment an equal re-bid would push the end back forever
+            AuctionKind::English {
+                reserve_price,
+                min_increment,
+                extension,
+            } => require!(
+                reserve_price > 0 && extension >= 0 && (extension == 0 || min_increment > 0),
+                MarketplaceError::InvalidAuction
+            ),
+            AuctionKind::Dutch {
+                start_price,
+                end_price,
+            } => require!(
+                end_price > 0 && start_price >= end_price,
+                MarketplaceError::InvalidAuction
+            ),
+        }
+
+        Ok(())
+    }
+
+    pub fn has_ended(&self, now: i64) -> bool {
+        now >= self.end_time
+    }
+
+    // The lowest bid an English auction accepts next
+    pub fn min_bid(&self) -> Result<u64> {
+        match self.kind {
+            AuctionKind::English {
+                reserve_price,
+                min_increment,
+                ..
+            } => match self.highest_bidder {
+                Some(_) => self
+                    .highest_bid
+                    .checked_add(min_increment)
+                    .ok_or(MarketplaceError::Overflow.into()),
+                None => Ok(reserve_price),
+            },
+            AuctionKind::Dutch { .. } => err!(MarketplaceError::InvalidAuction),
+        }
+    }
+
+    // The price a Dutch auction sells at `now`
+    pub fn current_price(&self, now: i64) -> Result<u64> {
+        match self.kind {
+            AuctionKind::Dutch {
+                start_price,
+                end_price,
+            } => {
+                let duration = (self.end_time - self.start_time) as u128;
+                let elapsed = now.clamp(self.start_time, self.end_time) - self.start_time;
+                let decay = (start_price - end_price) as u128 * elapsed as u128 / duration;
+
+                Ok(start_price - decay as u64)
+            }
+            AuctionKind::English { .. } => err!(MarketplaceError::InvalidAuction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english(min_increment: u64, extension: i64) -> AuctionKind {
+        AuctionKind::English {
+            reserve_price: 100,
+            min_increment,
+            extension,
+        }
+    }
+
+    #[test]
+    fn test_extension_requires_an_increment() {
+        assert!(Auction::validate(&english(0, 60), 0, 3600).is_err());
+        assert!(Auction::validate(&english(0, 0), 0, 3600).is_ok());
+        assert!(Auction::validate(&english(10, 60), 0, 3600).is_ok());
+    }
+
+    #[test]
+    fn test_equal_rebid_is_rejected() {
+        let kind = english(10, 60);
+        Auction::validate(&kind, 0, 3600).unwrap();
+
+        let auction = Auction {
+            seller: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            kind,
+            start_time: 0,
+            end_time: 3600,
+            highest_bidder: Some(Pubkey::new_unique()),
+            highest_bid: 150,
+            bump: 255,
+        };
+
+        // `place_bid` takes bids from `min_bid` up
+        assert!(auction.highest_bid < auction.min_bid().unwrap());
+        assert_eq!(auction.min_bid().unwrap(), 160);
+    }
+}
@@ -6,5 +6,9 @@ pub struct Listing {
     pub seller: Pubkey,
     pub mint: Pubkey,
     pub price: u64,
+    // the price is in lamports when unset, otherwise in base units of this mint
+    pub payment_mint: Option<Pubkey>,
+    // the NFT stays frozen in the seller's token account, with the listing as its delegate
+    pub delegated: bool,
     pub bump: u8,
 }
@@ -1,4 +1,16 @@
 use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+
+use crate::error::*;
+use crate::utils::basis_points;
+
+// A mint besides SOL that listings may be priced in
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq)]
+pub struct PaymentMint {
+    pub mint: Pubkey,
+    // reward tokens minted to each side of a trade in this mint, in basis points of the price
+    pub reward_rate_bps: u16,
+}
 
 #[account]
 #[derive(InitSpace)]
@@ -8,6 +20,132 @@ pub struct Marketplace {
     pub bump: u8,
     pub reward_bump: u8,
     pub treasury_bump: u8,
+    // when set, every purchase pays the verified creators their metadata royalties
+    pub enforce_royalties: bool,
+    // verified collections a listing may belong to, an empty list accepts any mint
+    #[max_len(8)]
+    pub allowed_collections: Vec<Pubkey>,
+    // mints besides SOL that listings may be priced in
+    #[max_len(8)]
+    pub payment_mints: Vec<PaymentMint>,
+    // reward tokens minted to the buyer and to the seller of a trade in SOL, in basis points of
+    // the price
+    pub reward_rate_bps: u16,
+    // buyers holding at least `discount_threshold` reward tokens get `fee_discount_bps` off the fee
+    pub discount_threshold: u64,
+    pub fee_discount_bps: u16,
     #[max_len(32)]
     pub name: String,
 }
+
+impl Marketplace {
+    pub const MAX_COLLECTIONS: usize = 8;
+    pub const MAX_PAYMENT_MINTS: usize = 8;
+
+    pub fn accepts(&self, metadata: &MetadataAccount) -> bool {
+        let collection = metadata
+            .collection
+            .as_ref()
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key);
+
+        self.accepts_collection(collection)
+    }
+
+    // `collection` must already be verified, as it is for Core assets
+    pub fn accepts_collection(&self, collection: Option<Pubkey>) -> bool {
+        self.allowed_collections.is_empty()
+            || collection.is_some_and(|collection| self.allowed_collections.contains(&collection))
+    }
+
+    pub fn check_listing(&self, price: u64, payment_mint: Option<Pubkey>) -> Result<()> {
+        require!(price > 0, MarketplaceError::InvalidPrice);
+        if let Some(payment_mint) = payment_mint {
+            require!(
+                self.payment_mints
+                    .iter()
+                    .any(|allowed| allowed.mint == payment_mint),
+                MarketplaceError::PaymentMintNotAllowed
+            );
+        }
+
+        Ok(())
+    }
+
+    // The marketplace fee on `price` for a buyer holding `rewards_held` reward tokens
+    pub fn fee_for(&self, price: u64, rewards_held: u64) -> Result<u64> {
+        let fee = basis_points(price, self.fees)?;
+        if self.fee_discount_bps == 0 || rewards_held < self.discount_threshold {
+            return Ok(fee);
+        }
+
+        Ok(fee - basis_points(fee, self.fee_discount_bps)?)
+    }
+
+    // Reward tokens minted to each side of a trade, at the rate of the mint it is priced in. Both
+    // sides together never get more than the fee paid
+    pub fn rewards_for(&self, payment_mint: Option<Pubkey>, price: u64, fee: u64) -> Result<u64> {
+        let rate = match payment_mint {
+            None => self.reward_rate_bps,
+            // a mint disallowed since the listing was made no longer earns rewards
+            Some(payment_mint) => self
+                .payment_mints
+                .iter()
+                .find(|allowed| allowed.mint == payment_mint)
+                .map_or(0, |allowed| allowed.reward_rate_bps),
+        };
+
+        Ok(basis_points(price, rate)?.min(fee / 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marketplace(payment_mints: Vec<PaymentMint>) -> Marketplace {
+        Marketplace {
+            admin: Pubkey::new_unique(),
+            fees: 500,
+            bump: 255,
+            reward_bump: 255,
+            treasury_bump: 255,
+            enforce_royalties: false,
+            allowed_collections: Vec::new(),
+            payment_mints,
+            reward_rate_bps: 100,
+            discount_threshold: 0,
+            fee_discount_bps: 0,
+            name: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rewards_follow_the_payment_mint() {
+        let usdc = Pubkey::new_unique();
+        let marketplace = marketplace(vec![PaymentMint {
+            mint: usdc,
+            reward_rate_bps: 50,
+        }]);
+
+        assert_eq!(marketplace.rewards_for(None, 10_000, 500).unwrap(), 100);
+        assert_eq!(
+            marketplace.rewards_for(Some(usdc), 10_000, 500).unwrap(),
+            50
+        );
+        // disallowed since the listing was made
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            marketplace.rewards_for(Some(other), 10_000, 500).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_rewards_stay_within_the_fee() {
+        let marketplace = marketplace(Vec::new());
+
+        // both sides are rewarded, so each gets at most half the fee
+        assert_eq!(marketplace.rewards_for(None, 10_000, 150).unwrap(), 75);
+    }
+}
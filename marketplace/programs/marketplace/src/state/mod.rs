@@ -5,3 +5,11 @@ pub use marketplace::*;
 pub mod listing;
 
 pub use listing::*;
+
+pub mod offer;
+
+pub use offer::*;
+
+pub mod auction;
+
+pub use auction::*;
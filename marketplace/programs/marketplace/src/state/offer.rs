@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+// A buyer's standing bid on a mint, the offered lamports sit in this account until it closes
+#[account]
+#[derive(InitSpace)]
+pub struct Offer {
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Offer {
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
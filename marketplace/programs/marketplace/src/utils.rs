@@ -0,0 +1,437 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::metadata::mpl_token_metadata::instructions::TransferV1CpiBuilder;
+use anchor_spl::metadata::{thaw_delegated_account, MetadataAccount, ThawDelegatedAccount};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use mpl_core::accounts::{BaseAssetV1, BaseCollectionV1};
+use mpl_core::fetch_plugin;
+use mpl_core::instructions::TransferV1CpiBuilder as CoreTransferV1CpiBuilder;
+use mpl_core::types::{Key as CoreKey, PluginType, Royalties, UpdateAuthority};
+
+use crate::constants::MPL_CORE_PROGRAM_ID;
+use crate::error::*;
+use crate::state::Marketplace;
+
+// Moves the NFT from the seller into a vault owned by a listing or an auction
+pub fn deposit_nft<'info>(
+    token_program: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    seller_ata: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        anchor_spl::token::TransferChecked {
+            from: seller_ata.clone(),
+            to: vault.clone(),
+            authority: seller.clone(),
+            mint: mint.clone(),
+        },
+    );
+
+    anchor_spl::token::transfer_checked(cpi_ctx, 1, 0)
+}
+
+// Thaws a delegated listing's token account, signed by the listing as its delegate
+pub fn thaw_nft<'info>(
+    metadata_program: &AccountInfo<'info>,
+    listing: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new_with_signer(
+        metadata_program.clone(),
+        ThawDelegatedAccount {
+            metadata: metadata_program.clone(),
+            delegate: listing.clone(),
+            token_account: token_account.clone(),
+            edition: edition.clone(),
+            mint: mint.clone(),
+            token_program: token_program.clone(),
+        },
+        signer_seeds,
+    );
+
+    thaw_delegated_account(cpi_ctx)
+}
+
+// Mints `amount` reward tokens to `to`, signed by the marketplace as the reward mint authority
+pub fn mint_rewards<'info>(
+    marketplace: &Account<'info, Marketplace>,
+    reward_mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let name = marketplace.name.as_bytes();
+    let seeds: &[&[u8]] = &[b"marketplace", name, &[marketplace.bump]];
+    let s = [seeds];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        anchor_spl::token::MintTo {
+            mint: reward_mint.clone(),
+            to: to.clone(),
+            authority: marketplace.to_account_info(),
+        },
+        &s,
+    );
+
+    anchor_spl::token::mint_to(cpi_ctx, amount)
+}
+
+// Mints both sides of a trade their reward tokens, trading with yourself earns none
+pub fn mint_trade_rewards<'info>(
+    marketplace: &Account<'info, Marketplace>,
+    reward_mint: &AccountInfo<'info>,
+    buyer_rewards: &Account<'info, anchor_spl::token::TokenAccount>,
+    seller_rewards: &Account<'info, anchor_spl::token::TokenAccount>,
+    token_program: &AccountInfo<'info>,
+    payment_mint: Option<Pubkey>,
+    price: u64,
+) -> Result<()> {
+    if buyer_rewards.owner == seller_rewards.owner {
+        return Ok(());
+    }
+
+    // the rewards are minted last, so this is still the balance the fee was discounted on
+    let fee = marketplace.fee_for(price, buyer_rewards.amount)?;
+    let rewards = marketplace.rewards_for(payment_mint, price, fee)?;
+
+    for to in [buyer_rewards, seller_rewards] {
+        mint_rewards(
+            marketplace,
+            reward_mint,
+            &to.to_account_info(),
+            token_program,
+            rewards,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Fees and royalties are expressed in basis points of the sale price
+pub fn basis_points(price: u64, bps: u16) -> Result<u64> {
+    let amount = (price as u128)
+        .checked_mul(bps as u128)
+        .ok_or(MarketplaceError::Overflow)?
+        / 10_000;
+
+    u64::try_from(amount).map_err(|_| MarketplaceError::Overflow.into())
+}
+
+// What each verified creator is owed out of `price`, following the metadata shares
+pub fn creator_royalties(metadata: &MetadataAccount, price: u64) -> Result<Vec<(Pubkey, u64)>> {
+    let royalty = basis_points(price, metadata.seller_fee_basis_points)?;
+
+    Ok(metadata
+        .creators
+        .iter()
+        .flatten()
+        .filter(|creator| creator.verified)
+        .map(|creator| {
+            (
+                creator.address,
+                (royalty as u128 * creator.share as u128 / 100) as u64,
+            )
+        })
+        .filter(|(_, amount)| *amount > 0)
+        .collect())
+}
+
+// Royalties are always paid when the marketplace enforces them, otherwise only when the buyer
+// passes the creators
+pub fn royalties_due(
+    marketplace: &Marketplace,
+    metadata: &MetadataAccount,
+    price: u64,
+    creators_passed: bool,
+) -> Result<Vec<(Pubkey, u64)>> {
+    royalties_due_from(marketplace, creators_passed, || {
+        creator_royalties(metadata, price)
+    })
+}
+
+// `royalties_due` for a Core asset, with the creators taken from its Royalties plugin
+pub fn core_royalties_due(
+    marketplace: &Marketplace,
+    asset: &AccountInfo,
+    collection: Option<&AccountInfo>,
+    price: u64,
+    creators_passed: bool,
+) -> Result<Vec<(Pubkey, u64)>> {
+    royalties_due_from(marketplace, creators_passed, || {
+        core_royalties(asset, collection, price)
+    })
+}
+
+fn royalties_due_from(
+    marketplace: &Marketplace,
+    creators_passed: bool,
+    royalties: impl FnOnce() -> Result<Vec<(Pubkey, u64)>>,
+) -> Result<Vec<(Pubkey, u64)>> {
+    if marketplace.enforce_royalties || creators_passed {
+        royalties()
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// Pays the royalties from `payer`, `creators` must hold one account per royalty in the same order
+pub fn pay_royalties<'info>(
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    royalties: &[(Pubkey, u64)],
+    creators: &[AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        creators.len() >= royalties.len(),
+        MarketplaceError::MissingCreatorAccount
+    );
+
+    let mut paid: u64 = 0;
+    for ((address, amount), creator) in royalties.iter().zip(creators) {
+        require_keys_eq!(
+            *address,
+            creator.key(),
+            MarketplaceError::InvalidCreatorAccount
+        );
+
+        let cpi_ctx = CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: payer.clone(),
+                to: creator.clone(),
+            },
+        );
+        transfer(cpi_ctx, *amount)?;
+
+        paid = paid
+            .checked_add(*amount)
+            .ok_or(MarketplaceError::Overflow)?;
+    }
+
+    Ok(paid)
+}
+
+// Token-priced variant of `pay_royalties`, `creator_atas` hold each creator's account for `mint`
+pub fn pay_token_royalties<'info>(
+    payer: &AccountInfo<'info>,
+    payer_ata: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    royalties: &[(Pubkey, u64)],
+    creator_atas: &'info [AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        creator_atas.len() >= royalties.len(),
+        MarketplaceError::MissingCreatorAccount
+    );
+
+    let mut paid: u64 = 0;
+    for ((address, amount), creator_ata) in royalties.iter().zip(creator_atas) {
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(creator_ata)?;
+        require!(
+            token_account.owner == *address && token_account.mint == mint.key(),
+            MarketplaceError::InvalidCreatorAccount
+        );
+
+        let cpi_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: payer_ata.to_account_info(),
+                mint: mint.to_account_info(),
+                to: creator_ata.clone(),
+                authority: payer.clone(),
+            },
+        );
+        transfer_checked(cpi_ctx, *amount, mint.decimals)?;
+
+        paid = paid
+            .checked_add(*amount)
+            .ok_or(MarketplaceError::Overflow)?;
+    }
+
+    Ok(paid)
+}
+
+// Pays the royalties straight out of a program-owned escrow account such as an `Offer`
+pub fn pay_royalties_from_escrow<'info>(
+    escrow: &AccountInfo<'info>,
+    royalties: &[(Pubkey, u64)],
+    creators: &[AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        creators.len() >= royalties.len(),
+        MarketplaceError::MissingCreatorAccount
+    );
+
+    let mut paid: u64 = 0;
+    for ((address, amount), creator) in royalties.iter().zip(creators) {
+        require_keys_eq!(
+            *address,
+            creator.key(),
+            MarketplaceError::InvalidCreatorAccount
+        );
+
+        escrow.sub_lamports(*amount)?;
+        creator.add_lamports(*amount)?;
+
+        paid = paid
+            .checked_add(*amount)
+            .ok_or(MarketplaceError::Overflow)?;
+    }
+
+    Ok(paid)
+}
+
+pub struct CoreAsset {
+    pub owner: Pubkey,
+    pub collection: Option<Pubkey>,
+}
+
+impl CoreAsset {
+    pub fn try_from_account(asset: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *asset.owner,
+            MPL_CORE_PROGRAM_ID,
+            MarketplaceError::InvalidCoreAsset
+        );
+
+        let asset = BaseAssetV1::from_bytes(&asset.try_borrow_data()?)
+            .map_err(|_| MarketplaceError::InvalidCoreAsset)?;
+        require!(
+            asset.key == CoreKey::AssetV1,
+            MarketplaceError::InvalidCoreAsset
+        );
+
+        let collection = match asset.update_authority {
+            UpdateAuthority::Collection(collection) => Some(collection),
+            _ => None,
+        };
+
+        Ok(Self {
+            owner: asset.owner,
+            collection,
+        })
+    }
+}
+
+// What each creator of a Core asset is owed out of `price`, following the Royalties plugin of
+// the asset, or of its collection when the asset has none
+pub fn core_royalties(
+    asset: &AccountInfo,
+    collection: Option<&AccountInfo>,
+    price: u64,
+) -> Result<Vec<(Pubkey, u64)>> {
+    let royalties = fetch_plugin::<BaseAssetV1, Royalties>(asset, PluginType::Royalties)
+        .ok()
+        .or_else(|| {
+            collection.and_then(|collection| {
+                fetch_plugin::<BaseCollectionV1, Royalties>(collection, PluginType::Royalties).ok()
+            })
+        });
+    let Some((_, royalties, _)) = royalties else {
+        return Ok(Vec::new());
+    };
+
+    let royalty = basis_points(price, royalties.basis_points)?;
+
+    Ok(royalties
+        .creators
+        .iter()
+        .map(|creator| {
+            (
+                creator.address,
+                (royalty as u128 * creator.percentage as u128 / 100) as u64,
+            )
+        })
+        .filter(|(_, amount)| *amount > 0)
+        .collect())
+}
+
+// Accounts for a Metaplex Core `TransferV1`
+pub struct CoreTransfer<'a, 'info> {
+    pub core_program: &'a AccountInfo<'info>,
+    pub asset: &'a AccountInfo<'info>,
+    // only for assets that belong to a collection
+    pub collection: Option<&'a AccountInfo<'info>>,
+    pub payer: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub new_owner: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CoreTransfer<'a, 'info> {
+    pub fn invoke_signed(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        CoreTransferV1CpiBuilder::new(self.core_program)
+            .asset(self.asset)
+            .collection(self.collection)
+            .payer(self.payer)
+            .authority(Some(self.authority))
+            .new_owner(self.new_owner)
+            .system_program(Some(self.system_program))
+            .invoke_signed(signer_seeds)
+            .map_err(Into::into)
+    }
+}
+
+// Accounts for a Token Metadata `TransferV1` of a programmable NFT
+pub struct ProgrammableTransfer<'a, 'info> {
+    pub metadata_program: &'a AccountInfo<'info>,
+    pub token: &'a AccountInfo<'info>,
+    pub token_owner: &'a AccountInfo<'info>,
+    pub destination_token: &'a AccountInfo<'info>,
+    pub destination_owner: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub token_record: &'a AccountInfo<'info>,
+    pub destination_token_record: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    // only needed when the NFT has a rule set
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> ProgrammableTransfer<'a, 'info> {
+    pub fn invoke_signed(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        TransferV1CpiBuilder::new(self.metadata_program)
+            .token(self.token)
+            .token_owner(self.token_owner)
+            .destination_token(self.destination_token)
+            .destination_owner(self.destination_owner)
+            .mint(self.mint)
+            .metadata(self.metadata)
+            .edition(Some(self.edition))
+            .token_record(Some(self.token_record))
+            .destination_token_record(Some(self.destination_token_record))
+            .authority(self.authority)
+            .payer(self.payer)
+            .system_program(self.system_program)
+            .sysvar_instructions(self.sysvar_instructions)
+            .spl_token_program(self.token_program)
+            .spl_ata_program(self.associated_token_program)
+            .authorization_rules_program(self.authorization_rules_program)
+            .authorization_rules(self.authorization_rules)
+            .amount(1)
+            .invoke_signed(signer_seeds)
+            .map_err(Into::into)
+    }
+}
@@ -1,16 +1,881 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  create,
+  fetchAsset,
+  mplCore,
+  ruleSet,
+} from "@metaplex-foundation/mpl-core";
+import {
+  createNft,
+  createProgrammableNft,
+  findMetadataPda,
+  MPL_TOKEN_METADATA_PROGRAM_ID,
+  mplTokenMetadata,
+  verifyCreatorV1,
+} from "@metaplex-foundation/mpl-token-metadata";
+import {
+  createSignerFromKeypair,
+  generateSigner,
+  keypairIdentity,
+  percentAmount,
+} from "@metaplex-foundation/umi";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import {
+  fromWeb3JsKeypair,
+  fromWeb3JsPublicKey,
+  toWeb3JsPublicKey,
+} from "@metaplex-foundation/umi-web3js-adapters";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { assert } from "chai";
 import { Marketplace } from "../target/types/marketplace";
 
 describe("marketplace", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.marketplace as Program<Marketplace>;
+  const TOKEN_PROGRAM_ID = anchor.utils.token.TOKEN_PROGRAM_ID;
+
+  const name = "test marketplace";
+  const [marketplace] = PublicKey.findProgramAddressSync(
+    [Buffer.from("marketplace"), Buffer.from(name)],
+    program.programId
+  );
+  const [treasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury"), marketplace.toBuffer()],
+    program.programId
+  );
+  const [rewardMint] = PublicKey.findProgramAddressSync(
+    [Buffer.from("rewards"), marketplace.toBuffer()],
+    program.programId
+  );
+
+  const umi = createUmi(provider.connection.rpcEndpoint)
+    .use(mplTokenMetadata())
+    .use(mplCore())
+    .use(
+      keypairIdentity(
+        fromWeb3JsKeypair((provider.wallet as anchor.Wallet).payer)
+      )
+    );
+
+  // minted to the provider wallet, which is also the only verified creator
+  const mintNft = async () => {
+    const mint = generateSigner(umi);
+    await createNft(umi, {
+      mint,
+      name: "NFT",
+      uri: "",
+      sellerFeeBasisPoints: percentAmount(5),
+    }).sendAndConfirm(umi);
+    return toWeb3JsPublicKey(mint.publicKey);
+  };
+
+  // Token Metadata transfers go past the default compute limit
+  const computeBudget = ComputeBudgetProgram.setComputeUnitLimit({
+    units: 400_000,
+  });
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: keypair.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      )
+    );
+    return keypair;
+  };
+
+  // for token accounts the program expects to exist already
+  const createAtaIx = (owner: PublicKey, mint: PublicKey) =>
+    new TransactionInstruction({
+      programId: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      keys: [
+        { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: true },
+        {
+          pubkey: anchor.utils.token.associatedAddress({ mint, owner }),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: owner, isSigner: false, isWritable: false },
+        { pubkey: mint, isSigner: false, isWritable: false },
+        {
+          pubkey: SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from([1]),
+    });
+
+  const ata = (owner: PublicKey, mint: PublicKey) =>
+    anchor.utils.token.associatedAddress({ mint, owner });
+
+  // the seller and the only verified creator are fresh wallets, so their shares
+  // of a sale can be told apart
+  const mintPnftForSale = async () => {
+    const seller = await fundedKeypair();
+    const creator = Keypair.generate();
+    const mint = generateSigner(umi);
+    await createProgrammableNft(umi, {
+      mint,
+      name: "pNFT",
+      uri: "",
+      sellerFeeBasisPoints: percentAmount(5),
+      tokenOwner: fromWeb3JsPublicKey(seller.publicKey),
+      creators: [
+        {
+          address: fromWeb3JsPublicKey(creator.publicKey),
+          verified: false,
+          share: 100,
+        },
+      ],
+    }).sendAndConfirm(umi);
+    await verifyCreatorV1(umi, {
+      metadata: findMetadataPda(umi, { mint: mint.publicKey }),
+      authority: createSignerFromKeypair(umi, fromWeb3JsKeypair(creator)),
+    }).sendAndConfirm(umi);
+
+    return {
+      seller,
+      creator: creator.publicKey,
+      mint: toWeb3JsPublicKey(mint.publicKey),
+    };
+  };
+
+  const tokenState = async (owner: PublicKey, mint: PublicKey) => {
+    const account = await provider.connection.getParsedAccountInfo(
+      ata(owner, mint)
+    );
+    return (account.value.data as anchor.web3.ParsedAccountData).parsed.info
+      .state;
+  };
+
+  const tokenBalance = async (owner: PublicKey, mint: PublicKey) => {
+    const balance = await provider.connection.getTokenAccountBalance(
+      ata(owner, mint)
+    );
+    return Number(balance.value.amount);
+  };
+
+  const listingPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [marketplace.toBuffer(), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const metadataProgram = toWeb3JsPublicKey(MPL_TOKEN_METADATA_PROGRAM_ID);
+  const metadataPda = (mint: PublicKey, ...seeds: string[]) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        metadataProgram.toBuffer(),
+        mint.toBuffer(),
+        ...seeds.map((seed) => Buffer.from(seed)),
+      ],
+      metadataProgram
+    )[0];
+  const editionPda = (mint: PublicKey) => metadataPda(mint, "edition");
+
+  // purchases of listings priced in SOL leave the payment mint accounts out
+  const solPayment = {
+    paymentMint: null,
+    buyerPaymentAta: null,
+    sellerPaymentAta: null,
+    treasuryPaymentAta: null,
+    paymentTokenProgram: null,
+  };
+
+  const offerPda = (mint: PublicKey, buyer: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offer"),
+        marketplace.toBuffer(),
+        mint.toBuffer(),
+        buyer.toBuffer(),
+      ],
+      program.programId
+    )[0];
+
+  // the validator clock, which offers and auctions expire against
+  const chainTime = async () =>
+    provider.connection.getBlockTime(await provider.connection.getSlot());
+
+  const waitUntil = async (timestamp: number) => {
+    while ((await chainTime()) < timestamp) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+  };
 
   it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    const tx = await program.methods
+      .initialize(name, 250)
+      .accounts({ tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
     console.log("Your transaction signature", tx);
+
+    const account = await program.account.marketplace.fetch(marketplace);
+    assert.ok(account.admin.equals(provider.wallet.publicKey));
+    assert.equal(account.fees, 250);
+  });
+
+  it("Lets the admin update the fee", async () => {
+    await program.methods.updateFee(500).accounts({ marketplace }).rpc();
+
+    const account = await program.account.marketplace.fetch(marketplace);
+    assert.equal(account.fees, 500);
+  });
+
+  it("Rejects a fee above 100%", async () => {
+    try {
+      await program.methods.updateFee(10_001).accounts({ marketplace }).rpc();
+      assert.fail("fee above 10000 basis points was accepted");
+    } catch (err) {
+      assert.include(String(err), "InvalidFee");
+    }
+  });
+
+  it("Manages the allowed collections", async () => {
+    const collection = anchor.web3.Keypair.generate().publicKey;
+
+    await program.methods
+      .allowCollection(collection)
+      .accounts({ marketplace })
+      .rpc();
+    let account = await program.account.marketplace.fetch(marketplace);
+    assert.equal(account.allowedCollections.length, 1);
+    assert.ok(account.allowedCollections[0].equals(collection));
+
+    try {
+      await program.methods
+        .allowCollection(collection)
+        .accounts({ marketplace })
+        .rpc();
+      assert.fail("the same collection was allowed twice");
+    } catch (err) {
+      assert.include(String(err), "CollectionAlreadyAllowed");
+    }
+
+    await program.methods
+      .disallowCollection(collection)
+      .accounts({ marketplace })
+      .rpc();
+    account = await program.account.marketplace.fetch(marketplace);
+    assert.equal(account.allowedCollections.length, 0);
+  });
+
+  it("Rejects disallowing a payment mint that was never allowed", async () => {
+    const paymentMint = anchor.web3.Keypair.generate().publicKey;
+
+    try {
+      await program.methods
+        .disallowPaymentMint(paymentMint)
+        .accounts({ marketplace })
+        .rpc();
+      assert.fail("an unknown payment mint was disallowed");
+    } catch (err) {
+      assert.include(String(err), "PaymentMintNotAllowed");
+    }
+  });
+
+  it("Configures trade rewards", async () => {
+    await program.methods
+      .setRewards(100, new anchor.BN(1_000_000), 5_000)
+      .accounts({ marketplace })
+      .rpc();
+
+    const account = await program.account.marketplace.fetch(marketplace);
+    assert.equal(account.rewardRateBps, 100);
+    assert.equal(account.discountThreshold.toNumber(), 1_000_000);
+    assert.equal(account.feeDiscountBps, 5_000);
+  });
+
+  it("Withdraws collected fees down to the rent-exempt minimum", async () => {
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    // stand in for fees collected by purchases
+    const fees = 1_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: treasury,
+          lamports: rentExempt + fees,
+        })
+      )
+    );
+
+    await program.methods
+      .withdrawTreasury(new anchor.BN(fees))
+      .accounts({ marketplace })
+      .rpc();
+    assert.equal(await provider.connection.getBalance(treasury), rentExempt);
+
+    try {
+      await program.methods
+        .withdrawTreasury(new anchor.BN(1))
+        .accounts({ marketplace })
+        .rpc();
+      assert.fail("withdrew below the rent-exempt minimum");
+    } catch (err) {
+      assert.include(String(err), "InsufficientTreasury");
+    }
+  });
+
+  it("Rewards both the buyer and the seller of a purchase", async () => {
+    const mint = await mintNft();
+    const buyer = await fundedKeypair();
+    const price = LAMPORTS_PER_SOL;
+
+    await program.methods
+      .listingNft(new anchor.BN(price), null)
+      .accountsPartial({ marketplace, sellerMint: mint })
+      .rpc();
+    await program.methods
+      .purchaseNft()
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        seller: provider.wallet.publicKey,
+        mint,
+        marketplace,
+        vaultAta: ata(listingPda(mint), mint),
+        sellerAta: null,
+        edition: null,
+        ...solPayment,
+      })
+      .preInstructions([createAtaIx(buyer.publicKey, mint)])
+      .signers([buyer])
+      .rpc();
+
+    // 1% of the price each, as configured above
+    const rewards = price / 100;
+    assert.equal(await tokenBalance(buyer.publicKey, mint), 1);
+    assert.equal(await tokenBalance(buyer.publicKey, rewardMint), rewards);
+    assert.equal(
+      await tokenBalance(provider.wallet.publicKey, rewardMint),
+      rewards
+    );
+  });
+
+  it("Relists a pNFT after unlisting it", async () => {
+    const mint = generateSigner(umi);
+    await createProgrammableNft(umi, {
+      mint,
+      name: "pNFT",
+      uri: "",
+      sellerFeeBasisPoints: percentAmount(5),
+    }).sendAndConfirm(umi);
+
+    const accounts = {
+      marketplace,
+      sellerMint: toWeb3JsPublicKey(mint.publicKey),
+      authorizationRulesProgram: null,
+      authorizationRules: null,
+    };
+    const [listing] = PublicKey.findProgramAddressSync(
+      [marketplace.toBuffer(), accounts.sellerMint.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .listProgrammableNft(new anchor.BN(1_000_000))
+      .accountsPartial(accounts)
+      .preInstructions([computeBudget])
+      .rpc();
+    await program.methods
+      .unlistProgrammableNft()
+      .accountsPartial(accounts)
+      .preInstructions([computeBudget])
+      .rpc();
+
+    // the vault of the first listing is out of the way of the second one
+    await program.methods
+      .listProgrammableNft(new anchor.BN(2_000_000))
+      .accountsPartial(accounts)
+      .preInstructions([computeBudget])
+      .rpc();
+
+    const account = await program.account.listing.fetch(listing);
+    assert.equal(account.price.toNumber(), 2_000_000);
+  });
+
+  it("Accepts an offer on an unlisted NFT", async () => {
+    const mint = await mintNft();
+    const buyer = await fundedKeypair();
+    const offer = offerPda(mint, buyer.publicKey);
+    const amount = LAMPORTS_PER_SOL;
+
+    await program.methods
+      .makeOffer(
+        new anchor.BN(amount),
+        new anchor.BN((await chainTime()) + 3600)
+      )
+      .accountsPartial({ buyer: buyer.publicKey, marketplace, mint, offer })
+      .signers([buyer])
+      .rpc();
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+
+    await program.methods
+      .acceptOffer()
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        marketplace,
+        mint,
+        offer,
+        sellerAta: ata(provider.wallet.publicKey, mint),
+        listing: null,
+        vault: null,
+        edition: null,
+      })
+      .rpc();
+
+    assert.equal(await tokenBalance(buyer.publicKey, mint), 1);
+    assert.isNull(await provider.connection.getAccountInfo(offer));
+    // the 5% fee set above, royalties are neither enforced nor passed
+    assert.equal(
+      (await provider.connection.getBalance(treasury)) - treasuryBefore,
+      amount / 20
+    );
+  });
+
+  it("Reclaims an offer only once it has expired", async () => {
+    const mint = await mintNft();
+    const buyer = await fundedKeypair();
+    const offer = offerPda(mint, buyer.publicKey);
+    const expiresAt = (await chainTime()) + 5;
+
+    await program.methods
+      .makeOffer(new anchor.BN(LAMPORTS_PER_SOL), new anchor.BN(expiresAt))
+      .accountsPartial({ buyer: buyer.publicKey, marketplace, mint, offer })
+      .signers([buyer])
+      .rpc();
+
+    try {
+      await program.methods
+        .reclaimOffer()
+        .accountsPartial({ buyer: buyer.publicKey, marketplace, offer })
+        .rpc();
+      assert.fail("reclaimed an offer before it expired");
+    } catch (err) {
+      assert.include(String(err), "OfferNotExpired");
+    }
+
+    await waitUntil(expiresAt);
+    try {
+      await program.methods
+        .acceptOffer()
+        .accountsPartial({
+          buyer: buyer.publicKey,
+          marketplace,
+          mint,
+          offer,
+          sellerAta: ata(provider.wallet.publicKey, mint),
+          listing: null,
+          vault: null,
+          edition: null,
+        })
+        .rpc();
+      assert.fail("accepted an expired offer");
+    } catch (err) {
+      assert.include(String(err), "OfferExpired");
+    }
+
+    await program.methods
+      .reclaimOffer()
+      .accountsPartial({ buyer: buyer.publicKey, marketplace, offer })
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(offer));
+    // the amount and the rent are back, the provider paid the fees
+    assert.equal(
+      await provider.connection.getBalance(buyer.publicKey),
+      10 * LAMPORTS_PER_SOL
+    );
+  });
+
+  it("Settles an English auction to the highest bidder", async () => {
+    const mint = await mintNft();
+    const [first, second] = [await fundedKeypair(), await fundedKeypair()];
+    const [auction] = PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), marketplace.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+    const endTime = (await chainTime()) + 10;
+
+    await program.methods
+      .createAuction(
+        {
+          english: {
+            reservePrice: new anchor.BN(LAMPORTS_PER_SOL / 2),
+            minIncrement: new anchor.BN(LAMPORTS_PER_SOL / 10),
+            extension: new anchor.BN(0),
+          },
+        },
+        new anchor.BN(endTime)
+      )
+      .accountsPartial({ marketplace, sellerMint: mint, auction })
+      .rpc();
+
+    await program.methods
+      .placeBid(new anchor.BN(LAMPORTS_PER_SOL / 2))
+      .accountsPartial({
+        bidder: first.publicKey,
+        previousBidder: null,
+        marketplace,
+        auction,
+      })
+      .signers([first])
+      .rpc();
+
+    // outbidding needs at least `min_increment` more
+    try {
+      await program.methods
+        .placeBid(new anchor.BN(LAMPORTS_PER_SOL / 2 + 1))
+        .accountsPartial({
+          bidder: second.publicKey,
+          previousBidder: first.publicKey,
+          marketplace,
+          auction,
+        })
+        .signers([second])
+        .rpc();
+      assert.fail("a bid under the minimum increment was accepted");
+    } catch (err) {
+      assert.include(String(err), "BidTooLow");
+    }
+
+    const bid = (LAMPORTS_PER_SOL * 6) / 10;
+    await program.methods
+      .placeBid(new anchor.BN(bid))
+      .accountsPartial({
+        bidder: second.publicKey,
+        previousBidder: first.publicKey,
+        marketplace,
+        auction,
+      })
+      .signers([second])
+      .rpc();
+    // the outbid bidder is refunded right away
+    assert.equal(
+      await provider.connection.getBalance(first.publicKey),
+      10 * LAMPORTS_PER_SOL
+    );
+
+    const settle = () =>
+      program.methods
+        .settleAuction()
+        .accountsPartial({
+          seller: provider.wallet.publicKey,
+          recipient: second.publicKey,
+          marketplace,
+          mint,
+          auction,
+        })
+        .rpc();
+    try {
+      await settle();
+      assert.fail("settled an auction before it ended");
+    } catch (err) {
+      assert.include(String(err), "AuctionNotEnded");
+    }
+
+    await waitUntil(endTime);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    await settle();
+
+    assert.equal(await tokenBalance(second.publicKey, mint), 1);
+    assert.isNull(await provider.connection.getAccountInfo(auction));
+    assert.equal(
+      (await provider.connection.getBalance(treasury)) - treasuryBefore,
+      bid / 20
+    );
+  });
+
+  it("Sells a delegated listing out of the seller's wallet", async () => {
+    const mint = await mintNft();
+    const buyer = await fundedKeypair();
+    const seller = provider.wallet.publicKey;
+
+    await program.methods
+      .listDelegatedNft(new anchor.BN(LAMPORTS_PER_SOL), null)
+      .accountsPartial({ marketplace, sellerMint: mint })
+      .rpc();
+    assert.equal(await tokenState(seller, mint), "frozen");
+
+    await program.methods
+      .purchaseNft()
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        seller,
+        mint,
+        marketplace,
+        vaultAta: null,
+        sellerAta: ata(seller, mint),
+        edition: editionPda(mint),
+        ...solPayment,
+      })
+      .preInstructions([createAtaIx(buyer.publicKey, mint)])
+      .signers([buyer])
+      .rpc();
+
+    assert.equal(await tokenBalance(buyer.publicKey, mint), 1);
+    assert.equal(await tokenBalance(seller, mint), 0);
+    assert.isNull(await provider.connection.getAccountInfo(listingPda(mint)));
+  });
+
+  it("Needs the edition to unlist a delegated listing", async () => {
+    const mint = await mintNft();
+    const seller = provider.wallet.publicKey;
+    const accounts = { marketplace, sellerMint: mint, vault: null };
+
+    await program.methods
+      .listDelegatedNft(new anchor.BN(LAMPORTS_PER_SOL), null)
+      .accountsPartial({ marketplace, sellerMint: mint })
+      .rpc();
+
+    // the seller's account stays frozen without the edition to thaw it
+    try {
+      await program.methods
+        .unlistNft()
+        .accountsPartial({ ...accounts, edition: null })
+        .rpc();
+      assert.fail("unlisted a delegated listing without its edition");
+    } catch (err) {
+      assert.include(String(err), "MissingListingAccounts");
+    }
+
+    await program.methods
+      .unlistNft()
+      .accountsPartial({ ...accounts, edition: editionPda(mint) })
+      .rpc();
+    assert.equal(await tokenState(seller, mint), "initialized");
+    assert.equal(await tokenBalance(seller, mint), 1);
+    assert.isNull(await provider.connection.getAccountInfo(listingPda(mint)));
+  });
+
+  it("Splits a pNFT sale between seller, treasury and creator", async () => {
+    const { seller, creator, mint } = await mintPnftForSale();
+    const buyer = await fundedKeypair();
+    const price = LAMPORTS_PER_SOL;
+    const listing = listingPda(mint);
+    const vault = ata(listing, mint);
+    const accounts = {
+      marketplace,
+      authorizationRulesProgram: null,
+      authorizationRules: null,
+    };
+
+    await program.methods
+      .listProgrammableNft(new anchor.BN(price))
+      .accountsPartial({
+        ...accounts,
+        seller: seller.publicKey,
+        sellerMint: mint,
+      })
+      .preInstructions([computeBudget])
+      .signers([seller])
+      .rpc();
+
+    const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    const rent =
+      (await provider.connection.getBalance(listing)) +
+      (await provider.connection.getBalance(vault));
+
+    await program.methods
+      .purchaseProgrammableNft()
+      .accountsPartial({
+        ...accounts,
+        buyer: buyer.publicKey,
+        seller: seller.publicKey,
+        mint,
+      })
+      .remainingAccounts([
+        { pubkey: creator, isSigner: false, isWritable: true },
+      ])
+      .preInstructions([computeBudget])
+      .signers([buyer])
+      .rpc();
+
+    assert.equal(await tokenBalance(buyer.publicKey, mint), 1);
+    // the emptied vault is closed along with the listing
+    assert.isNull(await provider.connection.getAccountInfo(vault));
+    // 5% fee and 5% royalties
+    assert.equal(
+      (await provider.connection.getBalance(treasury)) - treasuryBefore,
+      price / 20
+    );
+    assert.equal(await provider.connection.getBalance(creator), price / 20);
+    // Token Metadata may also hand back the vault's token record rent
+    assert.isAtLeast(
+      (await provider.connection.getBalance(seller.publicKey)) - sellerBefore,
+      price - price / 10 + rent
+    );
+  });
+
+  it("Splits a Core sale by the asset's Royalties plugin", async () => {
+    const seller = await fundedKeypair();
+    const creator = Keypair.generate().publicKey;
+    const buyer = await fundedKeypair();
+    const price = LAMPORTS_PER_SOL;
+    const asset = generateSigner(umi);
+    await create(umi, {
+      asset,
+      name: "Core",
+      uri: "",
+      owner: fromWeb3JsPublicKey(seller.publicKey),
+      plugins: [
+        {
+          type: "Royalties",
+          basisPoints: 500,
+          creators: [
+            { address: fromWeb3JsPublicKey(creator), percentage: 100 },
+          ],
+          ruleSet: ruleSet("None"),
+        },
+      ],
+    }).sendAndConfirm(umi);
+    const accounts = {
+      seller: seller.publicKey,
+      marketplace,
+      asset: toWeb3JsPublicKey(asset.publicKey),
+      collection: null,
+    };
+
+    await program.methods
+      .listCoreAsset(new anchor.BN(price))
+      .accountsPartial(accounts)
+      .signers([seller])
+      .rpc();
+
+    const listing = listingPda(accounts.asset);
+    const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    const rent = await provider.connection.getBalance(listing);
+
+    await program.methods
+      .purchaseCoreAsset()
+      .accountsPartial({ ...accounts, buyer: buyer.publicKey })
+      .remainingAccounts([
+        { pubkey: creator, isSigner: false, isWritable: true },
+      ])
+      .signers([buyer])
+      .rpc();
+
+    const bought = await fetchAsset(umi, asset.publicKey);
+    assert.equal(bought.owner.toString(), buyer.publicKey.toBase58());
+    assert.equal(
+      (await provider.connection.getBalance(treasury)) - treasuryBefore,
+      price / 20
+    );
+    assert.equal(await provider.connection.getBalance(creator), price / 20);
+    // the listing rent goes back to the seller with the proceeds
+    assert.equal(
+      (await provider.connection.getBalance(seller.publicKey)) - sellerBefore,
+      price - price / 10 + rent
+    );
+  });
+
+  it("Batch lists NFTs and sweeps them past a stale listing", async () => {
+    const seller = provider.wallet.publicKey;
+    const buyer = await fundedKeypair();
+    const mints = [await mintNft(), await mintNft(), await mintNft()];
+    const prices = [1, 2, 3].map((tenths) => (tenths * LAMPORTS_PER_SOL) / 10);
+    const account = (pubkey: PublicKey, isWritable = false) => ({
+      pubkey,
+      isSigner: false,
+      isWritable,
+    });
+
+    await program.methods
+      .batchList(prices.map((price) => new anchor.BN(price)), null)
+      .accountsPartial({ marketplace })
+      // the accounts of `List` for each NFT, in struct order
+      .remainingAccounts(
+        mints.flatMap((mint) => [
+          { pubkey: seller, isSigner: true, isWritable: true },
+          account(marketplace),
+          account(mint),
+          account(ata(seller, mint), true),
+          account(ata(listingPda(mint), mint), true),
+          account(listingPda(mint), true),
+          account(metadataPda(mint)),
+          account(SystemProgram.programId),
+          account(TOKEN_PROGRAM_ID),
+          account(anchor.utils.token.ASSOCIATED_PROGRAM_ID),
+          account(metadataProgram),
+        ])
+      )
+      .preInstructions([computeBudget])
+      .rpc();
+    for (const [i, mint] of mints.entries()) {
+      const listing = await program.account.listing.fetch(listingPda(mint));
+      assert.equal(listing.price.toNumber(), prices[i]);
+    }
+
+    // bought or unlisted after the buyer picked it
+    await program.methods
+      .unlistNft()
+      .accountsPartial({
+        marketplace,
+        sellerMint: mints[1],
+        vault: ata(listingPda(mints[1]), mints[1]),
+        edition: null,
+      })
+      .rpc();
+
+    const sweep = (maxTotal: number) =>
+      program.methods
+        .sweepPurchase(mints.length, new anchor.BN(maxTotal))
+        .accountsPartial({ buyer: buyer.publicKey, marketplace })
+        .remainingAccounts(
+          mints.flatMap((mint) => [
+            account(listingPda(mint), true),
+            account(seller, true),
+            account(mint),
+            account(ata(listingPda(mint), mint), true),
+            account(ata(buyer.publicKey, mint), true),
+            account(metadataPda(mint)),
+            account(ata(seller, rewardMint), true),
+          ])
+        )
+        .preInstructions([computeBudget])
+        .signers([buyer])
+        .rpc();
+
+    // the stale listing costs nothing, the other two 0.4 SOL together
+    const total = prices[0] + prices[2];
+    try {
+      await sweep(total - 1);
+      assert.fail("a sweep went over its max total");
+    } catch (err) {
+      assert.include(String(err), "SweepPriceExceeded");
+    }
+
+    const sellerRewards = await tokenBalance(seller, rewardMint);
+    await sweep(total);
+
+    assert.equal(await tokenBalance(buyer.publicKey, mints[0]), 1);
+    assert.equal(await tokenBalance(buyer.publicKey, mints[2]), 1);
+    assert.equal(await tokenBalance(seller, mints[1]), 1);
+    assert.equal(await tokenBalance(buyer.publicKey, rewardMint), total / 100);
+    assert.equal(
+      (await tokenBalance(seller, rewardMint)) - sellerRewards,
+      total / 100
+    );
   });
 });
@@ -2,3 +2,7 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// A multiplier of 100 earns the pool's base rate, 150 earns 1.5x
+#[constant]
+pub const MULTIPLIER_BASE: u16 = 100;
@@ -7,7 +7,25 @@ pub enum ErrorCode {
 }
 
 #[error_code]
-pub enum CustomError {
-    #[msg("Custom error message")]
-    Error1,
+pub enum StakeError {
+    #[msg("User already stakes the maximum number of NFTs")]
+    MaxStakeReached,
+    #[msg("NFT is still within its freeze period")]
+    FreezePeriodNotOver,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("No rewards to claim")]
+    NothingToClaim,
+    #[msg("Multiplier must be greater than zero")]
+    InvalidMultiplier,
+    #[msg("Pool has no multiplier root")]
+    MultipliersDisabled,
+    #[msg("Multiplier proof does not match the pool root")]
+    InvalidMultiplierProof,
+    #[msg("Pool has a multiplier root, NFTs must prove their multiplier")]
+    MultiplierRequired,
+    #[msg("NFT is not a verified member of the pool's collection")]
+    InvalidCollection,
+    #[msg("Only the program's upgrade authority can initialize the config")]
+    NotUpgradeAuthority,
 }
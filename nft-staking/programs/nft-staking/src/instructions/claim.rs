@@ -1,20 +1,19 @@
-use std::thread::sleep;
-
-use crate::state::{StakeAccount, StakeConfig, UserAccount};
-use anchor_lang::{prelude::*, solana_program::sysvar::rewards};
+use crate::error::StakeError;
+use crate::state::{PoolMember, StakeConfig, StakePool, UserAccount};
+use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{
-        mint_to, spl_token::instruction::transfer_checked, transfer, Mint, MintTo, Token,
-        TokenAccount,
-    },
+    token::{mint_to, Mint, MintTo, Token, TokenAccount},
 };
 
+// Checkpoints the user's position in one pool, then claims every point earned so far across
+// all pools, without unstaking
 #[derive(Accounts)]
 pub struct Claim<'info> {
     #[account[mut]]
     pub staker: Signer<'info>,
     #[account[
+      mut,
       seeds=[b"user", staker.key().as_ref()],
       bump= staker_account.bump
     ]]
@@ -25,22 +24,26 @@ pub struct Claim<'info> {
     ]]
     pub config: Account<'info, StakeConfig>,
     #[account[
-      mint::token_program = token_program
+      mut,
+      seeds=[b"pool", pool.collection_mint.as_ref()],
+      bump = pool.bump
     ]]
-    pub stake_token: Account<'info, Mint>,
+    pub pool: Account<'info, StakePool>,
     #[account[
-      seeds=[b"stake", staker.key().as_ref(), stake_token.key().as_ref() ],
-      bump = stake_account.bump
+      mut,
+      seeds=[b"member", pool.key().as_ref(), staker.key().as_ref()],
+      bump = pool_member.bump
     ]]
-    pub stake_account: Account<'info, StakeAccount>,
+    pub pool_member: Account<'info, PoolMember>,
     #[account[
+      mut,
       seeds = [b"reward", config.key().as_ref()],
       bump= config.rewards_bump,
       mint::authority = config
     ]]
     pub reward_mint: Account<'info, Mint>,
     #[account[
-      init,
+      init_if_needed,
       payer=staker,
       associated_token::mint=reward_mint,
       associated_token::authority=staker,
@@ -54,14 +57,23 @@ pub struct Claim<'info> {
 
 impl<'info> Claim<'info> {
     pub fn claim(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        let reward_index = self.pool.update_index(clock.unix_timestamp)?;
+        let earned = self
+            .pool_member
+            .accrue(reward_index, clock.unix_timestamp)?;
+        self.staker_account.add_points(earned)?;
+
+        let rewards = self.staker_account.points;
+        require!(rewards > 0, StakeError::NothingToClaim);
+
         // mint rewards to user as per user points
         let mint_rewards = MintTo {
             mint: self.reward_mint.to_account_info(),
-            to: self.staker.to_account_info(),
+            to: self.user_reward_mint_ata.to_account_info(),
             authority: self.config.to_account_info(),
         };
 
-        // unfreeze
         let signer_seeds: &[&[u8]] = &[
             b"config",           // Static byte seed
             &[self.config.bump], // `u8` bump converted to a slice
@@ -73,11 +85,10 @@ impl<'info> Claim<'info> {
                 mint_rewards,
                 &[signer_seeds],
             ),
-            self.staker_account.points as u64,
+            rewards,
         )?;
 
         self.staker_account.points = 0;
-        // update points
         Ok(())
     }
 }
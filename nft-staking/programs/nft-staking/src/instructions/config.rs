@@ -1,12 +1,23 @@
+use crate::error::StakeError;
 use crate::state::StakeConfig;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token};
 
+// Only the program's upgrade authority can claim the config, so nobody can front-run it
 #[derive(Accounts)]
 pub struct Config<'info> {
     #[account[mut]]
     pub admin: Signer<'info>,
+    #[account[
+      seeds = [crate::ID.as_ref()],
+      bump,
+      seeds::program = bpf_loader_upgradeable::ID,
+      constraint = program_data.upgrade_authority_address == Some(admin.key())
+        @ StakeError::NotUpgradeAuthority
+    ]]
+    pub program_data: Account<'info, ProgramData>,
     #[account[
       init,
       payer=admin,
@@ -30,20 +41,32 @@ pub struct Config<'info> {
 }
 
 impl<'info> Config<'info> {
-    pub fn init_config(
-        &mut self,
-        bumps: &ConfigBumps,
-        reward_per_token: u32,
-        max_stake: u32,
-        freeze_period: u32,
-    ) -> Result<()> {
+    pub fn init_config(&mut self, bumps: &ConfigBumps) -> Result<()> {
         self.config.set_inner(StakeConfig {
-            reward_per_token,
-            max_stake,
-            freeze_period,
+            admin: self.admin.key(),
             rewards_bump: bumps.reward_mint,
             bump: bumps.config,
         });
         Ok(())
     }
 }
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account[
+      mut,
+      has_one = admin,
+      seeds=[b"config"],
+      bump = config.bump
+    ]]
+    pub config: Account<'info, StakeConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    // Hands pool management over to `new_admin`
+    pub fn update_config(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.config.admin = new_admin;
+        Ok(())
+    }
+}
@@ -2,6 +2,10 @@ pub mod config;
 
 pub use config::*;
 
+pub mod pool;
+
+pub use pool::*;
+
 pub mod user;
 
 pub use user::*;
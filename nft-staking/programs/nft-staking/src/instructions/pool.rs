@@ -0,0 +1,86 @@
+use crate::state::{StakeConfig, StakePool};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct CreatePool<'info> {
+    #[account[mut]]
+    pub admin: Signer<'info>,
+    #[account[
+      has_one = admin,
+      seeds=[b"config"],
+      bump = config.bump
+    ]]
+    pub config: Account<'info, StakeConfig>,
+    pub collection_mint: Account<'info, Mint>,
+    #[account[
+      init,
+      payer=admin,
+      seeds=[b"pool", collection_mint.key().as_ref()],
+      bump,
+      space = 8 + StakePool::INIT_SPACE
+    ]]
+    pub pool: Account<'info, StakePool>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePool<'info> {
+    pub fn create_pool(
+        &mut self,
+        bumps: &CreatePoolBumps,
+        reward_per_token: u64,
+        max_stake: u32,
+        freeze_period: u32,
+    ) -> Result<()> {
+        self.pool.set_inner(StakePool {
+            collection_mint: self.collection_mint.key(),
+            reward_per_token,
+            max_stake,
+            freeze_period,
+            multiplier_root: None,
+            reward_index: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            bump: bumps.pool,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    pub admin: Signer<'info>,
+    #[account[
+      has_one = admin,
+      seeds=[b"config"],
+      bump = config.bump
+    ]]
+    pub config: Account<'info, StakeConfig>,
+    #[account[
+      mut,
+      seeds=[b"pool", pool.collection_mint.as_ref()],
+      bump = pool.bump
+    ]]
+    pub pool: Account<'info, StakePool>,
+}
+
+impl<'info> UpdatePool<'info> {
+    pub fn update_pool(
+        &mut self,
+        reward_per_token: u64,
+        max_stake: u32,
+        freeze_period: u32,
+    ) -> Result<()> {
+        // time before the change is still owed at the old rate
+        self.pool.update_index(Clock::get()?.unix_timestamp)?;
+        self.pool.reward_per_token = reward_per_token;
+        self.pool.max_stake = max_stake;
+        self.pool.freeze_period = freeze_period;
+        Ok(())
+    }
+
+    // Only new stakes are checked against the root, staked NFTs keep their multiplier
+    pub fn set_multiplier_root(&mut self, multiplier_root: Option<[u8; 32]>) -> Result<()> {
+        self.pool.multiplier_root = multiplier_root;
+        Ok(())
+    }
+}
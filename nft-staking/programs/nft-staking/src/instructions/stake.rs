@@ -4,7 +4,8 @@
 // stake it
 // freeze it
 
-use crate::state::{StakeAccount, StakeConfig, UserAccount};
+use crate::constants::MULTIPLIER_BASE;
+use crate::state::{PoolMember, StakeAccount, StakePool, UserAccount};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -12,10 +13,7 @@ use anchor_spl::{
         freeze_delegated_account, FreezeDelegatedAccount, MasterEditionAccount, Metadata,
         MetadataAccount,
     },
-    token::{
-        spl_token::instruction::transfer_checked, transfer, Mint, Token, TokenAccount,
-        TransferChecked,
-    },
+    token::{approve, Approve, Mint, Token, TokenAccount},
 };
 
 use crate::error::*;
@@ -29,11 +27,6 @@ pub struct Stake<'info> {
       bump= staker_account.bump
     ]]
     pub staker_account: Account<'info, UserAccount>,
-    #[account[
-      seeds=[b"config"],
-      bump = config.bump
-    ]]
-    pub config: Account<'info, StakeConfig>,
     #[account[
       init,
       payer=staker,
@@ -49,26 +42,36 @@ pub struct Stake<'info> {
     pub collection_mint: Account<'info, Mint>,
     #[account[
       mut,
-      associated_token::mint = stake_token,
-      associated_token::authority = staker,
+      has_one = collection_mint,
+      seeds=[b"pool", collection_mint.key().as_ref()],
+      bump = pool.bump
     ]]
-    pub user_stake_token_ata: Account<'info, TokenAccount>,
+    pub pool: Box<Account<'info, StakePool>>,
     #[account[
-      init,
+      init_if_needed,
       payer=staker,
+      seeds=[b"member", pool.key().as_ref(), staker.key().as_ref()],
+      bump,
+      space = 8 + PoolMember::INIT_SPACE
+    ]]
+    pub pool_member: Box<Account<'info, PoolMember>>,
+    #[account[
+      mut,
       associated_token::mint = stake_token,
-      associated_token::authority = config,
-      associated_token::token_program = token_program
+      associated_token::authority = staker,
     ]]
-    pub config_stake_token_ata: Account<'info, TokenAccount>,
+    pub user_stake_token_ata: Account<'info, TokenAccount>,
     #[account[
       seeds=[b"metadata", metadata_program.key().as_ref(), stake_token.key().as_ref()],
       seeds::program = metadata_program.key(),
       bump,
-      constraint = metadata.collection.as_ref().unwrap().key.as_ref() == collection_mint.key().as_ref(),
-      constraint = metadata.collection.as_ref().unwrap().verified == true
+      constraint = metadata
+        .collection
+        .as_ref()
+        .is_some_and(|collection| collection.key == collection_mint.key() && collection.verified)
+        @ StakeError::InvalidCollection
     ]]
-    pub metadata: Account<'info, MetadataAccount>,
+    pub metadata: Box<Account<'info, MetadataAccount>>,
     #[account(
         seeds = [
             b"metadata",
@@ -87,23 +90,75 @@ pub struct Stake<'info> {
 }
 
 impl<'info> Stake<'info> {
-    pub fn stake(&mut self, bumps: &StakeBumps) -> Result<()> {
+    // Without a root every NFT earns the pool's base rate, with one each NFT proves its multiplier
+    pub fn stake(
+        &mut self,
+        bumps: &StakeBumps,
+        multiplier: Option<u16>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         require!(
-            self.staker_account.amount_staked <= self.config.max_stake,
-            CustomError::Error1
+            self.pool_member.amount_staked < self.pool.max_stake,
+            StakeError::MaxStakeReached
         );
+        let multiplier = match multiplier {
+            Some(multiplier) => {
+                require!(multiplier > 0, StakeError::InvalidMultiplier);
+                self.pool
+                    .verify_multiplier(&self.stake_token.key(), multiplier, &proof)?;
+                multiplier
+            }
+            None => {
+                require!(
+                    self.pool.multiplier_root.is_none(),
+                    StakeError::MultiplierRequired
+                );
+                MULTIPLIER_BASE
+            }
+        };
         let clock = Clock::get()?; // Pull the clock sysvar
         let current_time = clock.unix_timestamp; // i64 in seconds
 
+        let reward_index = self.pool.update_index(current_time)?;
+        let earned = self.pool_member.accrue(reward_index, current_time)?;
+        self.staker_account.add_points(earned)?;
+        self.pool_member.amount_staked += 1;
+        self.pool_member.weight += multiplier as u64;
+        self.pool_member.bump = bumps.pool_member;
+        self.staker_account.amount_staked += 1;
+
         self.stake_account.set_inner(StakeAccount {
             stake_token: self.stake_token.key(),
             owner: self.staker.key(),
+            pool: self.pool.key(),
+            multiplier,
             staked_at: current_time,
+            unlock_at: current_time
+                .checked_add(self.pool.freeze_period as i64)
+                .ok_or(StakeError::Overflow)?,
             bump: bumps.stake_account,
         });
 
-        self.staker_account.amount_staked += 1;
-        // free nft authority till stake is over
+        // the stake account has to be the delegate before it can freeze the nft in place
+        let approve_accounts = Approve {
+            to: self.user_stake_token_ata.to_account_info(),
+            delegate: self.stake_account.to_account_info(),
+            authority: self.staker.to_account_info(),
+        };
+        approve(
+            CpiContext::new(self.token_program.to_account_info(), approve_accounts),
+            1,
+        )?;
+
+        let skey = self.staker.key();
+        let sskey = self.stake_token.key();
+        let seeds: &[&[u8]] = &[
+            b"stake",
+            skey.as_ref(),
+            sskey.as_ref(),
+            &[self.stake_account.bump],
+        ];
+        let s = [seeds];
 
         let freeze_accounts = FreezeDelegatedAccount {
             metadata: self.metadata.to_account_info(),
@@ -114,9 +169,11 @@ impl<'info> Stake<'info> {
             token_program: self.token_program.to_account_info(),
         };
 
-        let ctx = CpiContext::new(self.token_program.to_account_info(), freeze_accounts);
-        freeze_delegated_account(ctx);
-
-        Ok(())
+        let ctx = CpiContext::new_with_signer(
+            self.metadata_program.to_account_info(),
+            freeze_accounts,
+            &s,
+        );
+        freeze_delegated_account(ctx)
     }
 }
@@ -6,7 +6,7 @@
 // freeze it
 
 use crate::error::*;
-use crate::state::{StakeAccount, StakeConfig, UserAccount};
+use crate::state::{PoolMember, StakeAccount, StakePool, UserAccount};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -14,10 +14,7 @@ use anchor_spl::{
         thaw_delegated_account, MasterEditionAccount, Metadata, MetadataAccount,
         ThawDelegatedAccount,
     },
-    token::{
-        spl_token::instruction::transfer_checked, transfer, Mint, Token, TokenAccount,
-        TransferChecked,
-    },
+    token::{revoke, Mint, Revoke, Token, TokenAccount},
 };
 
 #[derive(Accounts)]
@@ -31,13 +28,11 @@ pub struct Unstake<'info> {
     ]]
     pub staker_account: Account<'info, UserAccount>,
     #[account[
-      seeds=[b"config"],
-      bump = config.bump
-    ]]
-    pub config: Account<'info, StakeConfig>,
-    #[account[
+      mut,
       seeds=[b"stake", staker.key().as_ref(), stake_token.key().as_ref() ],
-      bump = stake_account.bump
+      bump = stake_account.bump,
+      has_one = pool,
+      close = staker
     ]]
     pub stake_account: Account<'info, StakeAccount>,
     #[account[
@@ -45,6 +40,19 @@ pub struct Unstake<'info> {
     ]]
     pub stake_token: Account<'info, Mint>,
     pub collection_mint: Account<'info, Mint>,
+    #[account[
+      mut,
+      has_one = collection_mint,
+      seeds=[b"pool", collection_mint.key().as_ref()],
+      bump = pool.bump
+    ]]
+    pub pool: Box<Account<'info, StakePool>>,
+    #[account[
+      mut,
+      seeds=[b"member", pool.key().as_ref(), staker.key().as_ref()],
+      bump = pool_member.bump
+    ]]
+    pub pool_member: Box<Account<'info, PoolMember>>,
     #[account[
       mut,
       associated_token::mint = stake_token,
@@ -55,10 +63,13 @@ pub struct Unstake<'info> {
       seeds=[b"metadata", metadata_program.key().as_ref(), stake_token.key().as_ref()],
       seeds::program = metadata_program.key(),
       bump,
-      constraint = metadata.collection.as_ref().unwrap().key.as_ref() == collection_mint.key().as_ref(),
-      constraint = metadata.collection.as_ref().unwrap().verified == true
+      constraint = metadata
+        .collection
+        .as_ref()
+        .is_some_and(|collection| collection.key == collection_mint.key() && collection.verified)
+        @ StakeError::InvalidCollection
     ]]
-    pub metadata: Account<'info, MetadataAccount>,
+    pub metadata: Box<Account<'info, MetadataAccount>>,
     #[account(
         seeds = [
             b"metadata",
@@ -82,14 +93,17 @@ impl<'info> Unstake<'info> {
         let clock = Clock::get()?; // Pull the clock sysvar
         let current_time = clock.unix_timestamp; // i64 in seconds
 
-        let time_elapsed = current_time - self.stake_account.staked_at;
         require!(
-            ((time_elapsed / 86400) as u32) < self.config.freeze_period,
-            CustomError::Error1
+            current_time >= self.stake_account.unlock_at,
+            StakeError::FreezePeriodNotOver
         );
 
-        self.staker_account.points +=
-            ((time_elapsed / 86400) as u32) * self.config.reward_per_token;
+        let reward_index = self.pool.update_index(current_time)?;
+        let earned = self.pool_member.accrue(reward_index, current_time)?;
+        self.staker_account.add_points(earned)?;
+        self.pool_member.amount_staked -= 1;
+        self.pool_member.weight -= self.stake_account.multiplier as u64;
+        self.staker_account.amount_staked -= 1;
 
         let skey = &self.staker.key();
         let sskey = &self.stake_token.key();
@@ -116,9 +130,17 @@ impl<'info> Unstake<'info> {
         };
 
         let thaw_ctx =
-            CpiContext::new_with_signer(self.token_program.to_account_info(), thaw_account, s);
+            CpiContext::new_with_signer(self.metadata_program.to_account_info(), thaw_account, s);
+
+        thaw_delegated_account(thaw_ctx)?;
 
-        thaw_delegated_account(thaw_ctx);
-        Ok(())
+        let revoke_accounts = Revoke {
+            source: self.user_stake_token_ata.to_account_info(),
+            authority: self.staker.to_account_info(),
+        };
+        revoke(CpiContext::new(
+            self.token_program.to_account_info(),
+            revoke_accounts,
+        ))
     }
 }
@@ -3,6 +3,8 @@ pub mod constants;
 pub mod error;
 pub mod instructions;
 pub mod state;
+#[allow(clippy::module_inception)]
+mod tests;
 
 use anchor_lang::prelude::*;
 
@@ -16,8 +18,47 @@ declare_id!("DXeoeaJCo3nZF8rkj5UAHHDbpH139juTLfhH4T4S5xJW");
 pub mod nft_staking {
     use super::*;
 
-    pub fn stake(ctx: Context<Stake>) -> Result<()> {
-        ctx.accounts.stake(&ctx.bumps)
+    pub fn initialize_config(ctx: Context<Config>) -> Result<()> {
+        ctx.accounts.init_config(&ctx.bumps)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.update_config(new_admin)
+    }
+
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        reward_per_token: u64,
+        max_stake: u32,
+        freeze_period: u32,
+    ) -> Result<()> {
+        ctx.accounts
+            .create_pool(&ctx.bumps, reward_per_token, max_stake, freeze_period)
+    }
+
+    pub fn update_pool(
+        ctx: Context<UpdatePool>,
+        reward_per_token: u64,
+        max_stake: u32,
+        freeze_period: u32,
+    ) -> Result<()> {
+        ctx.accounts
+            .update_pool(reward_per_token, max_stake, freeze_period)
+    }
+
+    pub fn set_multiplier_root(
+        ctx: Context<UpdatePool>,
+        multiplier_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.set_multiplier_root(multiplier_root)
+    }
+
+    pub fn initialize_user(ctx: Context<User>) -> Result<()> {
+        ctx.accounts.create_user_account(ctx.bumps)
+    }
+
+    pub fn stake(ctx: Context<Stake>, multiplier: Option<u16>, proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.stake(&ctx.bumps, multiplier, proof)
     }
 
     pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
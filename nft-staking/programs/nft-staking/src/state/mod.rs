@@ -6,6 +6,14 @@ pub mod stake_config;
 
 pub use stake_config::*;
 
+pub mod stake_pool;
+
+pub use stake_pool::*;
+
+pub mod pool_member;
+
+pub use pool_member::*;
+
 pub mod user_account;
 
 pub use user_account::*;
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MULTIPLIER_BASE;
+use crate::error::StakeError;
+
+// A user's position in one pool
+#[account]
+#[derive(InitSpace)]
+pub struct PoolMember {
+    pub amount_staked: u32,
+    // sum of the multipliers of the NFTs staked, in MULTIPLIER_BASE units
+    pub weight: u64,
+    // the pool's reward_index when this position was last checkpointed
+    pub reward_index: u128,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl PoolMember {
+    // Returns the rewards earned since the last checkpoint against the pool's advanced
+    // `reward_index` and checkpoints it, before `weight` changes
+    pub fn accrue(&mut self, reward_index: u128, now: i64) -> Result<u64> {
+        let earned = reward_index.saturating_sub(self.reward_index) * self.weight as u128
+            / MULTIPLIER_BASE as u128;
+
+        self.reward_index = reward_index;
+        self.last_update = now;
+        u64::try_from(earned).map_err(|_| error!(StakeError::Overflow))
+    }
+}
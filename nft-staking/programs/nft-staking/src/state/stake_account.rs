@@ -5,6 +5,11 @@ use anchor_lang::prelude::*;
 pub struct StakeAccount {
     pub stake_token: Pubkey,
     pub owner: Pubkey,
+    pub pool: Pubkey,
+    // weight the NFT adds to the owner's pool position, in MULTIPLIER_BASE units
+    pub multiplier: u16,
     pub staked_at: i64,
+    // fixed at stake time, so later changes to the pool's freeze period only apply to new stakes
+    pub unlock_at: i64,
     pub bump: u8,
 }
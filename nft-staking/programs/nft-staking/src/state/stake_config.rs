@@ -1,11 +1,11 @@
 use anchor_lang::prelude::*;
 
+// Program-wide settings, reward rates and limits live on each StakePool
 #[account]
 #[derive(InitSpace)]
 pub struct StakeConfig {
-    pub reward_per_token: u32,
-    pub max_stake: u32,
-    pub freeze_period: u32,
+    // the only key allowed to create and update pools
+    pub admin: Pubkey,
     pub rewards_bump: u8,
     pub bump: u8,
 }
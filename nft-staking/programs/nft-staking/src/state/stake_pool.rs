@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::error::StakeError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakePool {
+    // every NFT staked in the pool is a verified member of this collection
+    pub collection_mint: Pubkey,
+    // reward token base units a 1x NFT earns per second
+    pub reward_per_token: u64,
+    pub max_stake: u32,
+    // seconds an NFT stays locked after it is staked
+    pub freeze_period: u32,
+    // merkle root of (mint, multiplier) leaves, unset when the pool has no multipliers
+    pub multiplier_root: Option<[u8; 32]>,
+    // reward token base units a 1x NFT has earned since the pool was created
+    pub reward_index: u128,
+    // when reward_index was last advanced
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl StakePool {
+    // Advances the index at the current rate, before the rate or a member's weight changes
+    pub fn update_index(&mut self, now: i64) -> Result<u128> {
+        let elapsed = now.saturating_sub(self.last_update).max(0) as u128;
+        self.reward_index = self
+            .reward_index
+            .checked_add(elapsed * self.reward_per_token as u128)
+            .ok_or(StakeError::Overflow)?;
+        self.last_update = now;
+        Ok(self.reward_index)
+    }
+
+    // Checks that `mint` earns `multiplier` against the admin-set root. Leaves are
+    // hash(mint, multiplier le bytes) and each pair of nodes is hashed in sorted order
+    pub fn verify_multiplier(
+        &self,
+        mint: &Pubkey,
+        multiplier: u16,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let root = self
+            .multiplier_root
+            .ok_or(StakeError::MultipliersDisabled)?;
+
+        let leaf = hashv(&[mint.as_ref(), &multiplier.to_le_bytes()]).to_bytes();
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            }
+        });
+
+        require!(computed == root, StakeError::InvalidMultiplierProof);
+        Ok(())
+    }
+}
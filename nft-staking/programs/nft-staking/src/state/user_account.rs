@@ -1,9 +1,23 @@
 use anchor_lang::prelude::*;
 
+use crate::error::StakeError;
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserAccount {
-    pub points: u32,
+    // reward tokens checkpointed from the user's pools and not claimed yet
+    pub points: u64,
+    // NFTs staked across all pools
     pub amount_staked: u32,
     pub bump: u8,
 }
+
+impl UserAccount {
+    pub fn add_points(&mut self, earned: u64) -> Result<()> {
+        self.points = self
+            .points
+            .checked_add(earned)
+            .ok_or(StakeError::Overflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,720 @@
+#[cfg(test)]
+mod tests {
+
+    use {
+        anchor_lang::{
+            prelude::Clock,
+            solana_program::{bpf_loader_upgradeable, hash::hashv, program_pack::Pack},
+            AccountDeserialize, InstructionData, ToAccountMetas,
+        },
+        anchor_spl::{
+            associated_token::{get_associated_token_address, spl_associated_token_account},
+            metadata::mpl_token_metadata::{
+                self,
+                accounts::{MasterEdition, Metadata},
+                instructions::{CreateV1Builder, MintV1Builder, VerifyCollectionV1Builder},
+                types::{Collection, CollectionDetails, PrintSupply, TokenStandard},
+            },
+            token::spl_token,
+        },
+        litesvm::LiteSVM,
+        solana_account::Account,
+        solana_instruction::Instruction,
+        solana_keypair::Keypair,
+        solana_message::Message,
+        solana_native_token::LAMPORTS_PER_SOL,
+        solana_pubkey::Pubkey,
+        solana_signer::Signer,
+        solana_transaction::Transaction,
+        solana_transaction_error::TransactionError,
+        std::path::PathBuf,
+    };
+
+    static PROGRAM_ID: Pubkey = crate::ID;
+
+    const REWARD_PER_TOKEN: u64 = 10;
+    const MAX_STAKE: u32 = 2;
+    const FREEZE_PERIOD: u32 = 100;
+
+    struct Setup {
+        program: LiteSVM,
+        staker: Keypair,
+        config: Pubkey,
+        pool: Pubkey,
+        pool_member: Pubkey,
+        user_account: Pubkey,
+        reward_mint: Pubkey,
+        collection_mint: Pubkey,
+        nft_mint: Pubkey,
+    }
+
+    fn send(program: &mut LiteSVM, payer: &Keypair, signers: &[&Keypair], ixs: &[Instruction]) {
+        let message = Message::new(ixs, Some(&payer.pubkey()));
+        let transaction = Transaction::new(signers, message, program.latest_blockhash());
+        program.send_transaction(transaction).unwrap();
+    }
+
+    fn warp_to(program: &mut LiteSVM, unix_timestamp: i64) {
+        let mut clock = program.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        program.set_sysvar::<Clock>(&clock);
+    }
+
+    fn program_data() -> Pubkey {
+        Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0
+    }
+
+    // LiteSVM loads programs without the upgradeable loader, so the program data account naming
+    // the upgrade authority is written by hand: the `ProgramData` tag, the deploy slot and the
+    // authority, with no code after them
+    fn set_upgrade_authority(program: &mut LiteSVM, authority: Pubkey) {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+
+        program
+            .set_account(
+                program_data(),
+                Account {
+                    lamports: LAMPORTS_PER_SOL,
+                    data,
+                    owner: bpf_loader_upgradeable::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    fn init_config_ix(admin: Pubkey) -> Instruction {
+        let config = Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0;
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Config {
+                admin,
+                program_data: program_data(),
+                config,
+                reward_mint: Pubkey::find_program_address(
+                    &[b"reward", config.as_ref()],
+                    &PROGRAM_ID,
+                )
+                .0,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::InitializeConfig {}.data(),
+        }
+    }
+
+    // Creates a master edition NFT owned by `owner`, optionally in an unverified collection
+    fn create_nft(
+        program: &mut LiteSVM,
+        owner: &Keypair,
+        name: &str,
+        collection: Option<Pubkey>,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let mut create = CreateV1Builder::new();
+        create
+            .metadata(Metadata::find_pda(&mint.pubkey()).0)
+            .master_edition(Some(MasterEdition::find_pda(&mint.pubkey()).0))
+            .mint(mint.pubkey(), true)
+            .authority(owner.pubkey())
+            .payer(owner.pubkey())
+            .update_authority(owner.pubkey(), true)
+            .spl_token_program(Some(spl_token::ID))
+            .name(name.to_string())
+            .uri("https://example.com/nft.json".to_string())
+            .seller_fee_basis_points(0)
+            .token_standard(TokenStandard::NonFungible)
+            .print_supply(PrintSupply::Zero);
+        match collection {
+            Some(key) => create.collection(Collection {
+                verified: false,
+                key,
+            }),
+            None => create.collection_details(CollectionDetails::V1 { size: 0 }),
+        };
+
+        let mint_to = MintV1Builder::new()
+            .token(get_associated_token_address(
+                &owner.pubkey(),
+                &mint.pubkey(),
+            ))
+            .token_owner(Some(owner.pubkey()))
+            .metadata(Metadata::find_pda(&mint.pubkey()).0)
+            .master_edition(Some(MasterEdition::find_pda(&mint.pubkey()).0))
+            .mint(mint.pubkey())
+            .authority(owner.pubkey())
+            .payer(owner.pubkey())
+            .amount(1)
+            .instruction();
+
+        send(
+            program,
+            owner,
+            &[owner, &mint],
+            &[create.instruction(), mint_to],
+        );
+        mint.pubkey()
+    }
+
+    fn load_programs() -> LiteSVM {
+        let mut program = LiteSVM::new();
+
+        // Load program SO file
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let program_data = std::fs::read(manifest_dir.join("../../target/deploy/nft_staking.so"))
+            .expect("Failed to read program SO file");
+        program.add_program(PROGRAM_ID, &program_data);
+
+        // dumped from mainnet by `tests/fetch-fixtures.sh`, see the README
+        let metadata_data =
+            std::fs::read(manifest_dir.join("../../tests/fixtures/mpl_token_metadata.so"))
+                .expect("Failed to read Token Metadata SO file");
+        program.add_program(mpl_token_metadata::ID, &metadata_data);
+
+        program
+    }
+
+    fn setup() -> Setup {
+        let mut program = load_programs();
+        let staker = Keypair::new();
+        program
+            .airdrop(&staker.pubkey(), 100 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to staker");
+        set_upgrade_authority(&mut program, staker.pubkey());
+
+        let config = Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0;
+        let reward_mint =
+            Pubkey::find_program_address(&[b"reward", config.as_ref()], &PROGRAM_ID).0;
+        let user_account =
+            Pubkey::find_program_address(&[b"user", staker.pubkey().as_ref()], &PROGRAM_ID).0;
+
+        let init_user_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::User {
+                user: staker.pubkey(),
+                user_account,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::InitializeUser {}.data(),
+        };
+        send(
+            &mut program,
+            &staker,
+            &[&staker],
+            &[init_config_ix(staker.pubkey()), init_user_ix],
+        );
+
+        let collection_mint = create_nft(&mut program, &staker, "Collection", None);
+        let nft_mint = create_nft(&mut program, &staker, "Staked", Some(collection_mint));
+
+        let verify_ix = VerifyCollectionV1Builder::new()
+            .authority(staker.pubkey())
+            .metadata(Metadata::find_pda(&nft_mint).0)
+            .collection_mint(collection_mint)
+            .collection_metadata(Some(Metadata::find_pda(&collection_mint).0))
+            .collection_master_edition(Some(MasterEdition::find_pda(&collection_mint).0))
+            .instruction();
+
+        let pool =
+            Pubkey::find_program_address(&[b"pool", collection_mint.as_ref()], &PROGRAM_ID).0;
+        let pool_member = Pubkey::find_program_address(
+            &[b"member", pool.as_ref(), staker.pubkey().as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let create_pool_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::CreatePool {
+                admin: staker.pubkey(),
+                config,
+                collection_mint,
+                pool,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::CreatePool {
+                reward_per_token: REWARD_PER_TOKEN,
+                max_stake: MAX_STAKE,
+                freeze_period: FREEZE_PERIOD,
+            }
+            .data(),
+        };
+        send(
+            &mut program,
+            &staker,
+            &[&staker],
+            &[verify_ix, create_pool_ix],
+        );
+
+        Setup {
+            program,
+            staker,
+            config,
+            pool,
+            pool_member,
+            user_account,
+            reward_mint,
+            collection_mint,
+            nft_mint,
+        }
+    }
+
+    fn stake_account(setup: &Setup) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"stake",
+                setup.staker.pubkey().as_ref(),
+                setup.nft_mint.as_ref(),
+            ],
+            &PROGRAM_ID,
+        )
+        .0
+    }
+
+    fn stake_ix(setup: &Setup, multiplier: Option<u16>, proof: Vec<[u8; 32]>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Stake {
+                staker: setup.staker.pubkey(),
+                staker_account: setup.user_account,
+                stake_account: stake_account(setup),
+                stake_token: setup.nft_mint,
+                collection_mint: setup.collection_mint,
+                pool: setup.pool,
+                pool_member: setup.pool_member,
+                user_stake_token_ata: get_associated_token_address(
+                    &setup.staker.pubkey(),
+                    &setup.nft_mint,
+                ),
+                metadata: Metadata::find_pda(&setup.nft_mint).0,
+                edition: MasterEdition::find_pda(&setup.nft_mint).0,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                metadata_program: mpl_token_metadata::ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Stake { multiplier, proof }.data(),
+        }
+    }
+
+    fn unstake_ix(setup: &Setup) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Unstake {
+                staker: setup.staker.pubkey(),
+                staker_account: setup.user_account,
+                stake_account: stake_account(setup),
+                stake_token: setup.nft_mint,
+                collection_mint: setup.collection_mint,
+                pool: setup.pool,
+                pool_member: setup.pool_member,
+                user_stake_token_ata: get_associated_token_address(
+                    &setup.staker.pubkey(),
+                    &setup.nft_mint,
+                ),
+                metadata: Metadata::find_pda(&setup.nft_mint).0,
+                edition: MasterEdition::find_pda(&setup.nft_mint).0,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                metadata_program: mpl_token_metadata::ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Unstake {}.data(),
+        }
+    }
+
+    fn claim_ix(setup: &Setup) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Claim {
+                staker: setup.staker.pubkey(),
+                staker_account: setup.user_account,
+                config: setup.config,
+                pool: setup.pool,
+                pool_member: setup.pool_member,
+                reward_mint: setup.reward_mint,
+                user_reward_mint_ata: get_associated_token_address(
+                    &setup.staker.pubkey(),
+                    &setup.reward_mint,
+                ),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: spl_token::ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::Claim {}.data(),
+        }
+    }
+
+    // Leaves and sorted node pairs hashed the same way as StakePool::verify_multiplier
+    fn multiplier_leaf(mint: &Pubkey, multiplier: u16) -> [u8; 32] {
+        hashv(&[mint.as_ref(), &multiplier.to_le_bytes()]).to_bytes()
+    }
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    fn set_multiplier_root_ix(setup: &Setup, admin: Pubkey, root: Option<[u8; 32]>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdatePool {
+                admin,
+                config: setup.config,
+                pool: setup.pool,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::SetMultiplierRoot {
+                multiplier_root: root,
+            }
+            .data(),
+        }
+    }
+
+    fn update_pool_ix(setup: &Setup, reward_per_token: u64, freeze_period: u32) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdatePool {
+                admin: setup.staker.pubkey(),
+                config: setup.config,
+                pool: setup.pool,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::UpdatePool {
+                reward_per_token,
+                max_stake: MAX_STAKE,
+                freeze_period,
+            }
+            .data(),
+        }
+    }
+
+    fn pool_member(setup: &Setup) -> crate::state::PoolMember {
+        let account = setup.program.get_account(&setup.pool_member).unwrap();
+        crate::state::PoolMember::try_deserialize(&mut account.data.as_ref()).unwrap()
+    }
+
+    fn user_account(setup: &Setup) -> crate::state::UserAccount {
+        let account = setup.program.get_account(&setup.user_account).unwrap();
+        crate::state::UserAccount::try_deserialize(&mut account.data.as_ref()).unwrap()
+    }
+
+    fn reward_balance(setup: &Setup) -> u64 {
+        let ata = get_associated_token_address(&setup.staker.pubkey(), &setup.reward_mint);
+        let account = setup.program.get_account(&ata).unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    fn send_setup_ix(setup: &mut Setup, ix: Instruction) -> Result<(), TransactionError> {
+        // a fresh blockhash keeps repeated instructions from looking like duplicate transactions
+        setup.program.expire_blockhash();
+        let message = Message::new(&[ix], Some(&setup.staker.pubkey()));
+        let transaction =
+            Transaction::new(&[&setup.staker], message, setup.program.latest_blockhash());
+        setup
+            .program
+            .send_transaction(transaction)
+            .map(|_| ())
+            .map_err(|failed| failed.err)
+    }
+
+    #[test]
+    fn test_stake_freezes_the_nft() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        let ata = get_associated_token_address(&setup.staker.pubkey(), &setup.nft_mint);
+        let account = setup.program.get_account(&ata).unwrap();
+        let token_account = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert!(token_account.is_frozen());
+        assert_eq!(token_account.delegate, Some(stake_account(&setup)).into());
+
+        assert_eq!(user_account(&setup).amount_staked, 1);
+        let member = pool_member(&setup);
+        assert_eq!(member.amount_staked, 1);
+        assert_eq!(member.weight, crate::MULTIPLIER_BASE as u64);
+        assert_eq!(member.last_update, 1_000);
+    }
+
+    #[test]
+    fn test_claim_accrues_per_second_without_unstaking() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        warp_to(&mut setup.program, 1_030);
+        let ix = claim_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(reward_balance(&setup), 30 * REWARD_PER_TOKEN);
+
+        assert_eq!(user_account(&setup).points, 0);
+        assert_eq!(pool_member(&setup).last_update, 1_030);
+        assert_eq!(pool_member(&setup).amount_staked, 1);
+
+        // only the time since the last checkpoint is paid again
+        warp_to(&mut setup.program, 1_045);
+        let ix = claim_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(reward_balance(&setup), 45 * REWARD_PER_TOKEN);
+    }
+
+    #[test]
+    fn test_unstake_is_locked_during_the_freeze_period() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        warp_to(&mut setup.program, 1_000 + FREEZE_PERIOD as i64 - 1);
+        let ix = unstake_ix(&setup);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+
+        warp_to(&mut setup.program, 1_000 + FREEZE_PERIOD as i64);
+        let ix = unstake_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        let ata = get_associated_token_address(&setup.staker.pubkey(), &setup.nft_mint);
+        let account = setup.program.get_account(&ata).unwrap();
+        let token_account = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert!(!token_account.is_frozen());
+        assert!(token_account.delegate.is_none());
+        assert!(setup
+            .program
+            .get_account(&stake_account(&setup))
+            .is_none_or(|account| account.lamports == 0));
+
+        // rewards are checkpointed on unstake and stop accruing afterwards
+        let user = user_account(&setup);
+        assert_eq!(user.amount_staked, 0);
+        assert_eq!(user.points, FREEZE_PERIOD as u64 * REWARD_PER_TOKEN);
+        assert_eq!(pool_member(&setup).weight, 0);
+
+        warp_to(&mut setup.program, 5_000);
+        let ix = claim_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(
+            reward_balance(&setup),
+            FREEZE_PERIOD as u64 * REWARD_PER_TOKEN
+        );
+    }
+
+    #[test]
+    fn test_freeze_period_is_fixed_at_stake_time() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        // a longer freeze set afterwards does not extend the running lock
+        let ix = update_pool_ix(&setup, REWARD_PER_TOKEN, FREEZE_PERIOD * 10);
+        send_setup_ix(&mut setup, ix).unwrap();
+        warp_to(&mut setup.program, 1_000 + FREEZE_PERIOD as i64);
+        let ix = unstake_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        // and a shorter one does not cut it short
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+        let ix = update_pool_ix(&setup, REWARD_PER_TOKEN, 0);
+        send_setup_ix(&mut setup, ix).unwrap();
+        warp_to(&mut setup.program, 1_000 + FREEZE_PERIOD as i64 + 1);
+        let ix = unstake_ix(&setup);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+    }
+
+    #[test]
+    fn test_claim_rejects_empty_rewards() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        // nothing has accrued in the same second the NFT was staked
+        let ix = claim_ix(&setup);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+    }
+
+    #[test]
+    fn test_multiplier_scales_the_pool_rate() {
+        let mut setup = setup();
+        let leaf = multiplier_leaf(&setup.nft_mint, 150);
+        let sibling = multiplier_leaf(&Pubkey::new_unique(), 200);
+
+        let ix = set_multiplier_root_ix(
+            &setup,
+            setup.staker.pubkey(),
+            Some(hash_pair(leaf, sibling)),
+        );
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        // an NFT without a proof, or with a multiplier the root does not hold, is rejected
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+
+        let ix = stake_ix(&setup, Some(300), vec![sibling]);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+
+        let ix = stake_ix(&setup, Some(150), vec![sibling]);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(pool_member(&setup).weight, 150);
+
+        warp_to(&mut setup.program, 1_020);
+        let ix = claim_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(reward_balance(&setup), 20 * REWARD_PER_TOKEN * 150 / 100);
+    }
+
+    #[test]
+    fn test_multipliers_need_a_root() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+
+        let ix = stake_ix(&setup, Some(150), vec![]);
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+    }
+
+    #[test]
+    fn test_rate_changes_only_apply_going_forward() {
+        let mut setup = setup();
+        warp_to(&mut setup.program, 1_000);
+        let ix = stake_ix(&setup, None, vec![]);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        // the first 30 seconds are still owed at the old rate
+        warp_to(&mut setup.program, 1_030);
+        let ix = update_pool_ix(&setup, 1, FREEZE_PERIOD);
+        send_setup_ix(&mut setup, ix).unwrap();
+
+        warp_to(&mut setup.program, 1_050);
+        let ix = claim_ix(&setup);
+        send_setup_ix(&mut setup, ix).unwrap();
+        assert_eq!(reward_balance(&setup), 30 * REWARD_PER_TOKEN + 20);
+    }
+
+    #[test]
+    fn test_update_pool_checks_the_admin() {
+        let mut setup = setup();
+        let other = Keypair::new();
+        setup
+            .program
+            .airdrop(&other.pubkey(), LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let (config, pool) = (setup.config, setup.pool);
+        let update_ix = |admin: Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdatePool {
+                admin,
+                config,
+                pool,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::UpdatePool {
+                reward_per_token: 1,
+                max_stake: 1,
+                freeze_period: 1,
+            }
+            .data(),
+        };
+
+        let message = Message::new(&[update_ix(other.pubkey())], Some(&other.pubkey()));
+        let transaction = Transaction::new(&[&other], message, setup.program.latest_blockhash());
+        assert!(setup.program.send_transaction(transaction).is_err());
+
+        let message = Message::new(
+            &[set_multiplier_root_ix(
+                &setup,
+                other.pubkey(),
+                Some([1; 32]),
+            )],
+            Some(&other.pubkey()),
+        );
+        let transaction = Transaction::new(&[&other], message, setup.program.latest_blockhash());
+        assert!(setup.program.send_transaction(transaction).is_err());
+
+        let ix = update_ix(setup.staker.pubkey());
+        send_setup_ix(&mut setup, ix).unwrap();
+        let account = setup.program.get_account(&setup.pool).unwrap();
+        let pool = crate::state::StakePool::try_deserialize(&mut account.data.as_ref()).unwrap();
+        assert_eq!(pool.reward_per_token, 1);
+        assert_eq!(pool.multiplier_root, None);
+    }
+
+    #[test]
+    fn test_initialize_config_needs_the_upgrade_authority() {
+        let mut program = load_programs();
+        let (authority, other) = (Keypair::new(), Keypair::new());
+        for payer in [&authority, &other] {
+            program.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        }
+        set_upgrade_authority(&mut program, authority.pubkey());
+
+        let message = Message::new(&[init_config_ix(other.pubkey())], Some(&other.pubkey()));
+        let transaction = Transaction::new(&[&other], message, program.latest_blockhash());
+        assert!(program.send_transaction(transaction).is_err());
+
+        send(
+            &mut program,
+            &authority,
+            &[&authority],
+            &[init_config_ix(authority.pubkey())],
+        );
+    }
+
+    #[test]
+    fn test_update_config_hands_over_the_admin() {
+        let mut setup = setup();
+        let new_admin = Keypair::new();
+        setup
+            .program
+            .airdrop(&new_admin.pubkey(), LAMPORTS_PER_SOL)
+            .unwrap();
+
+        let config = setup.config;
+        let update_ix = |admin: Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateConfig { admin, config }.to_account_metas(None),
+            data: crate::instruction::UpdateConfig {
+                new_admin: new_admin.pubkey(),
+            }
+            .data(),
+        };
+
+        // only the current admin can hand it over
+        let message = Message::new(&[update_ix(new_admin.pubkey())], Some(&new_admin.pubkey()));
+        let transaction =
+            Transaction::new(&[&new_admin], message, setup.program.latest_blockhash());
+        assert!(setup.program.send_transaction(transaction).is_err());
+
+        let ix = update_ix(setup.staker.pubkey());
+        send_setup_ix(&mut setup, ix).unwrap();
+        let account = setup.program.get_account(&setup.config).unwrap();
+        let config =
+            crate::state::StakeConfig::try_deserialize(&mut account.data.as_ref()).unwrap();
+        assert_eq!(config.admin, new_admin.pubkey());
+
+        // the old admin no longer manages pools
+        let ix = set_multiplier_root_ix(&setup, setup.staker.pubkey(), Some([1; 32]));
+        assert!(send_setup_ix(&mut setup, ix).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+#!/usr/bin/env sh
+# Dumps the mainnet programs the LiteSVM tests load. `*.so` is gitignored, so a fresh clone has none
+set -e
+
+cd "$(dirname "$0")"
+mkdir -p fixtures
+solana program dump -u m metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s fixtures/mpl_token_metadata.so
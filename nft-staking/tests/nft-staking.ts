@@ -1,16 +1,148 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { assert } from "chai";
 import { NftStaking } from "../target/types/nft_staking";
 
 describe("nft-staking", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.NftStaking as Program<NftStaking>;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+  const [config] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  // `anchor test` deploys the program upgradeable, with the provider wallet as its authority
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  const [userAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  // Pools are keyed by a collection mint, a bare mint is enough to create one
+  const collectionMint = Keypair.generate();
+  const [pool] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pool"), collectionMint.publicKey.toBuffer()],
+    program.programId
+  );
+
+  before(async () => {
+    const wallet = provider.wallet.publicKey;
+    const initializeMint2 = new TransactionInstruction({
+      programId: anchor.utils.token.TOKEN_PROGRAM_ID,
+      keys: [
+        { pubkey: collectionMint.publicKey, isSigner: false, isWritable: true },
+      ],
+      // InitializeMint2 with 0 decimals, the wallet as mint authority and no freeze authority
+      data: Buffer.concat([
+        Buffer.from([20, 0]),
+        wallet.toBuffer(),
+        Buffer.from([0]),
+      ]),
+    });
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: wallet,
+        newAccountPubkey: collectionMint.publicKey,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(82),
+        space: 82,
+        programId: anchor.utils.token.TOKEN_PROGRAM_ID,
+      }),
+      initializeMint2
+    );
+    await provider.sendAndConfirm(tx, [collectionMint]);
+  });
+
+  it("Initializes the config", async () => {
+    const tx = await program.methods
+      .initializeConfig()
+      .accountsPartial({ programData })
+      .rpc();
     console.log("Your transaction signature", tx);
+
+    const account = await program.account.stakeConfig.fetch(config);
+    assert.ok(account.admin.equals(provider.wallet.publicKey));
+  });
+
+  it("Hands the config over to a new admin and back", async () => {
+    const newAdmin = Keypair.generate();
+
+    await program.methods.updateConfig(newAdmin.publicKey).rpc();
+    let account = await program.account.stakeConfig.fetch(config);
+    assert.ok(account.admin.equals(newAdmin.publicKey));
+
+    await program.methods
+      .updateConfig(provider.wallet.publicKey)
+      .accounts({ admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    account = await program.account.stakeConfig.fetch(config);
+    assert.ok(account.admin.equals(provider.wallet.publicKey));
+  });
+
+  it("Creates a pool for a collection", async () => {
+    await program.methods
+      .createPool(new anchor.BN(10), 5, 1)
+      .accounts({ collectionMint: collectionMint.publicKey })
+      .rpc();
+
+    const account = await program.account.stakePool.fetch(pool);
+    assert.ok(account.collectionMint.equals(collectionMint.publicKey));
+    assert.equal(account.rewardPerToken.toNumber(), 10);
+    assert.equal(account.maxStake, 5);
+    assert.equal(account.freezePeriod, 1);
+    assert.isNull(account.multiplierRoot);
+  });
+
+  it("Lets the admin update the pool", async () => {
+    await program.methods
+      .updatePool(new anchor.BN(20), 10, 2)
+      .accounts({ pool })
+      .rpc();
+    await program.methods
+      .setMultiplierRoot(Array(32).fill(7))
+      .accounts({ pool })
+      .rpc();
+
+    const account = await program.account.stakePool.fetch(pool);
+    assert.equal(account.rewardPerToken.toNumber(), 20);
+    assert.equal(account.maxStake, 10);
+    assert.equal(account.freezePeriod, 2);
+    assert.deepEqual(account.multiplierRoot, Array(32).fill(7));
+  });
+
+  it("Rejects pool updates from anyone else", async () => {
+    const other = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .updatePool(new anchor.BN(1), 1, 1)
+        .accounts({ admin: other.publicKey, pool })
+        .signers([other])
+        .rpc();
+      assert.fail("a non-admin updated the pool");
+    } catch (err) {
+      assert.include(String(err), "ConstraintHasOne");
+    }
+  });
+
+  it("Initializes a user account", async () => {
+    await program.methods.initializeUser().rpc();
+
+    const account = await program.account.userAccount.fetch(userAccount);
+    assert.equal(account.points.toNumber(), 0);
+    assert.equal(account.amountStaked, 0);
   });
 });